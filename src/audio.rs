@@ -1,10 +1,21 @@
-use std::{io, path};
+use crate::synth;
+use std::{io, path, str::FromStr};
 
 use kira::{
-    AudioManager, AudioManagerSettings, DefaultBackend,
-    sound::static_sound::{StaticSoundData, StaticSoundHandle},
+    AudioManager, AudioManagerSettings, Decibels, Tween,
+    sound::{
+        PlaybackState,
+        static_sound::{StaticSoundData, StaticSoundHandle},
+    },
+    track::{TrackBuilder, TrackHandle},
 };
 
+// tests run without a sound card
+#[cfg(not(test))]
+type Backend = kira::DefaultBackend;
+#[cfg(test)]
+type Backend = kira::backend::mock::MockBackend;
+
 #[derive(Copy, Clone, Eq, Hash, PartialEq)]
 pub enum Sound {
     Die = 0,
@@ -12,43 +23,387 @@ pub enum Sound {
     EatGhost,
     ExtraLives,
     OpeningSong,
+    Intermission,
+    Siren,
+    Eating,
+    EatingShort,
+    Fright,
+    Eyes,
+}
+
+// Every sound plays on one of the channels - each has its own volume
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Channel {
+    Music = 0,
+    Effects,
+    Siren,
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "music" => Ok(Channel::Music),
+            "effects" => Ok(Channel::Effects),
+            "siren" => Ok(Channel::Siren),
+            _ => Err(format!(
+                "Unknown channel: {s} (expected music, effects or siren)"
+            )),
+        }
+    }
+}
+
+impl Sound {
+    fn channel(self) -> Channel {
+        match self {
+            Sound::OpeningSong | Sound::Intermission => Channel::Music,
+            Sound::Siren | Sound::Fright | Sound::Eyes => Channel::Siren,
+            _ => Channel::Effects,
+        }
+    }
+
+    // While a sound with priority plays the siren and the eating sounds are cut - and the
+    // dying sound comes before the music
+    fn priority(self) -> u8 {
+        match self {
+            Sound::Die => 2,
+            Sound::OpeningSong | Sound::Intermission => 1,
+            _ => 0,
+        }
+    }
+}
+
+// The background siren - it rises in steps as the board is cleared, and has its own sound
+// while the ghosts are frightened and while their eyes return home
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Siren {
+    Off,
+    Normal(u32), // step 0..SIREN_STEPS
+    Fright,
+    Eyes,
+}
+
+pub const SIREN_STEPS: u32 = 5;
+
+// Sounds are panned by the column where they happen - left edge of the board to the right
+pub fn pan(col: usize, width: usize) -> f32 {
+    2.0 * col as f32 / (width.max(2) - 1) as f32 - 1.0
+}
+
+// the ghost cues are faint, and each ghost has its own pitch
+const CUE_VOLUME: f32 = -24.0;
+const CUE_RATE: [f64; 4] = [1.5, 1.25, 1.0, 0.75];
+
+// Where the sounds come from - the audio files, the synthesized arcade sounds, or the Atari VCS
+// sounds (synthesized, with the VCS tune from its file)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SoundSet {
+    Files,
+    Synth,
+    Vcs,
 }
 
 const AUDIO_DIR: &str = "Audio";
+const VCS_TUNE: &str = "vcs_90.ogg";
+
+// the sounds, in the order of AUDIO_FILES
+const SOUNDS: [Sound; 11] = [
+    Sound::Die,
+    Sound::EatPill,
+    Sound::EatGhost,
+    Sound::ExtraLives,
+    Sound::OpeningSong,
+    Sound::Intermission,
+    Sound::Siren,
+    Sound::Eating,
+    Sound::EatingShort,
+    Sound::Fright,
+    Sound::Eyes,
+];
 
-const AUDIO_FILES: [&str; 5] = [
+const AUDIO_FILES: [&str; 11] = [
     "die.ogg",
     "eatpill.ogg",
     "eatghost.ogg",
     "extra_lives.ogg",
     "opening_song.ogg",
+    "intermission.ogg",
+    "siren.ogg",
+    "eating.ogg",
+    "eating.short.ogg",
+    "fright.ogg",
+    "eyes.ogg",
 ];
 
 pub struct AM {
-    manager: AudioManager<DefaultBackend>,
+    _manager: AudioManager<Backend>, // the channels play through it
+    channels: [TrackHandle; 3],
     sounds: [StaticSoundData; AUDIO_FILES.len()],
+    playing: Vec<(Sound, StaticSoundHandle)>, // sounds with priority
+    siren: Siren,
+    siren_handle: Option<StaticSoundHandle>,
+    waka: Option<StaticSoundHandle>,
+    waka_short: bool,
+    cues: Option<[Option<StaticSoundHandle>; 4]>, // None - ghost cues are off
 }
 
-impl Default for AM {
-    fn default() -> Self {
-        let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
+impl AM {
+    // the sounds are synthesized when there are no audio files - or just the missing ones
+    pub fn new(set: SoundSet) -> Self {
+        let mut manager = AudioManager::<Backend>::new(AudioManagerSettings::default())
             .expect("Failed to create AM");
+        let channels = [(); 3].map(|_| {
+            manager
+                .add_sub_track(TrackBuilder::new())
+                .expect("Failed to create audio channel")
+        });
 
-        let sounds = AUDIO_FILES.map(|audio_file| {
+        let load = |audio_file| {
             let path = path::Path::new(AUDIO_DIR).join(audio_file);
 
             StaticSoundData::from_file(&path)
                 .unwrap_or_else(|e| panic!("Failed to load sound: {path:?}: {e}"))
+        };
+        let files = path::Path::new(AUDIO_DIR).is_dir();
+        let sounds = match set {
+            SoundSet::Files if files => SOUNDS.map(|s| match AUDIO_FILES[s as usize] {
+                f if path::Path::new(AUDIO_DIR).join(f).is_file() => load(f),
+                _ => synth::sound(s),
+            }),
+            SoundSet::Vcs => SOUNDS.map(|s| match s {
+                Sound::OpeningSong if files => load(VCS_TUNE),
+                _ => synth::vcs_sound(s),
+            }),
+            _ => SOUNDS.map(synth::sound),
+        };
+
+        AM {
+            _manager: manager,
+            channels,
+            sounds,
+            playing: Vec::new(),
+            siren: Siren::Off,
+            siren_handle: None,
+            waka: None,
+            waka_short: false,
+            cues: None,
+        }
+    }
+
+    pub fn play(&mut self, name: Sound) -> io::Result<()> {
+        self.play_at(name, 0.0)
+    }
+
+    // play panned to the left (-1) or right (1). A sound with priority is not played over one
+    // with a higher priority, and cuts those with a lower one short
+    pub fn play_at(&mut self, name: Sound, pan: f32) -> io::Result<()> {
+        let priority = name.priority();
+        if priority == 0 {
+            self.start(name, pan)?;
+            return Ok(());
+        }
+        self.playing
+            .retain(|(_, h)| h.state() != PlaybackState::Stopped);
+        if self.playing.iter().any(|(s, _)| s.priority() > priority) {
+            return Ok(());
+        }
+        self.playing.retain_mut(|(s, h)| {
+            if s.priority() < priority {
+                h.stop(Tween::default());
+            }
+            s.priority() >= priority
         });
+        let handle = self.start(name, pan)?;
+        self.siren(Siren::Off)?;
+        self.stop_waka();
+        self.ghost_cues([None; 4])?;
+        self.playing.push((name, handle));
+        Ok(())
+    }
+
+    fn start(&mut self, name: Sound, pan: f32) -> io::Result<StaticSoundHandle> {
+        self.channels[name.channel() as usize]
+            .play(self.sounds[name as usize].panning(pan))
+            .map_err(io::Error::other)
+    }
+
+    // true while a sound with priority is playing
+    fn busy(&mut self) -> bool {
+        self.playing
+            .retain(|(_, h)| h.state() != PlaybackState::Stopped);
+        !self.playing.is_empty()
+    }
+
+    // stop all sounds on a channel
+    pub fn stop(&mut self, channel: Channel) {
+        self.playing.retain_mut(|(s, h)| {
+            if s.channel() == channel {
+                h.stop(Tween::default());
+            }
+            s.channel() != channel
+        });
+        if channel == Channel::Siren {
+            if let Some(mut handle) = self.siren_handle.take() {
+                handle.stop(Tween::default());
+            }
+            self.siren = Siren::Off;
+        }
+        if channel == Channel::Effects {
+            self.stop_waka();
+            if let Some(cues) = self.cues.as_mut() {
+                for mut handle in cues.iter_mut().filter_map(Option::take) {
+                    handle.stop(Tween::default());
+                }
+            }
+        }
+    }
+
+    // volume in percent
+    pub fn set_volume(&mut self, channel: Channel, volume: u8) {
+        let db = match volume {
+            0 => Decibels::SILENCE,
+            v => Decibels(20.0 * (v.min(100) as f32 / 100.0).log10()),
+        };
+        self.channels[channel as usize].set_volume(db, Tween::default());
+    }
+
+    pub fn pause(&mut self) {
+        for c in self.channels.iter_mut() {
+            c.pause(Tween::default());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        for c in self.channels.iter_mut() {
+            c.resume(Tween::default());
+        }
+    }
+
+    // waka-waka while eating dots - the two sounds take turns, and a new one only starts when
+    // the last one has finished, so they don't pile up when pacman is fast
+    pub fn waka(&mut self) -> io::Result<()> {
+        if self.busy()
+            || self
+                .waka
+                .as_ref()
+                .is_some_and(|h| h.state() == PlaybackState::Playing)
+        {
+            return Ok(());
+        }
+        let sound = if self.waka_short {
+            Sound::EatingShort
+        } else {
+            Sound::Eating
+        };
+        self.waka_short = !self.waka_short;
+        self.waka = Some(self.start(sound, 0.0)?);
+        Ok(())
+    }
+
+    pub fn enable_ghost_cues(&mut self) {
+        self.cues = Some([None, None, None, None]);
+    }
+
+    // a faint loop for each ghost out in the maze (Some(pan)), following it from side to side
+    pub fn ghost_cues(&mut self, ghosts: [Option<f32>; 4]) -> io::Result<()> {
+        let ghosts = if self.busy() { [None; 4] } else { ghosts };
+        let Some(cues) = self.cues.as_mut() else {
+            return Ok(());
+        };
+        for (i, (cue, pan)) in cues.iter_mut().zip(ghosts).enumerate() {
+            match (cue.as_mut(), pan) {
+                (Some(handle), Some(pan)) => handle.set_panning(pan, Tween::default()),
+                (Some(handle), None) => {
+                    handle.stop(Tween::default());
+                    *cue = None;
+                }
+                (None, Some(pan)) => {
+                    let data = self.sounds[Sound::Siren as usize]
+                        .loop_region(..)
+                        .playback_rate(CUE_RATE[i])
+                        .volume(CUE_VOLUME)
+                        .panning(pan);
+                    *cue = Some(
+                        self.channels[Channel::Effects as usize]
+                            .play(data)
+                            .map_err(io::Error::other)?,
+                    );
+                }
+                (None, None) => (),
+            }
+        }
+        Ok(())
+    }
 
-        AM { manager, sounds }
+    pub fn stop_waka(&mut self) {
+        if let Some(mut handle) = self.waka.take() {
+            handle.stop(Tween::default());
+        }
+        self.waka_short = false;
+    }
+
+    // switch the looping siren - moving up a step only changes the pitch of the playing loop
+    pub fn siren(&mut self, siren: Siren) -> io::Result<()> {
+        let siren = if self.busy() { Siren::Off } else { siren };
+        if siren == self.siren {
+            return Ok(());
+        }
+        let rate = |siren| match siren {
+            Siren::Normal(step) => 1.0 + 0.12 * step as f64,
+            _ => 1.0,
+        };
+        match (self.siren, siren, self.siren_handle.as_mut()) {
+            (Siren::Normal(_), Siren::Normal(_), Some(handle)) => {
+                handle.set_playback_rate(rate(siren), Tween::default());
+            }
+            _ => {
+                if let Some(mut handle) = self.siren_handle.take() {
+                    handle.stop(Tween::default());
+                }
+                let sound = match siren {
+                    Siren::Off => None,
+                    Siren::Normal(_) => Some(Sound::Siren),
+                    Siren::Fright => Some(Sound::Fright),
+                    Siren::Eyes => Some(Sound::Eyes),
+                };
+                if let Some(sound) = sound {
+                    let data = self.sounds[sound as usize]
+                        .loop_region(..)
+                        .playback_rate(rate(siren));
+                    self.siren_handle = Some(
+                        self.channels[Channel::Siren as usize]
+                            .play(data)
+                            .map_err(io::Error::other)?,
+                    );
+                }
+            }
+        }
+        self.siren = siren;
+        Ok(())
     }
 }
 
-impl AM {
-    pub fn play(&mut self, name: Sound) -> Result<StaticSoundHandle, std::io::Error> {
-        self.manager
-            .play(self.sounds[name as usize].clone())
-            .map_err(io::Error::other)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pan() {
+        assert_eq!(pan(0, 28), -1.0);
+        assert_eq!(pan(27, 28), 1.0);
+        assert!(pan(13, 28) < 0.0 && pan(14, 28) > 0.0);
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut am = AM::new(SoundSet::Synth);
+        let playing = |am: &AM| am.playing.iter().map(|(s, _)| *s).collect::<Vec<_>>();
+        am.play(Sound::Intermission).unwrap();
+        am.play(Sound::Die).unwrap();
+        assert!(playing(&am) == [Sound::Die]);
+        // no music over pacman dying
+        am.play(Sound::OpeningSong).unwrap();
+        assert!(playing(&am) == [Sound::Die]);
     }
 }
@@ -1,4 +1,5 @@
 use crate::maze::*;
+use crate::path::{DistanceField, DistanceTable, Junctions};
 use std::ops::{Index, IndexMut};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -30,6 +31,10 @@ impl Position {
         Position(row * WIDTH + col)
     }
 
+    pub const fn index(&self) -> usize {
+        self.0
+    }
+
     pub const fn col(&self) -> usize {
         self.0 % WIDTH
     }
@@ -38,6 +43,11 @@ impl Position {
         self.0 / WIDTH
     }
 
+    // signed (col, row) - for targets that may lie outside the maze
+    pub const fn tile(&self) -> (isize, isize) {
+        (self.col() as isize, self.row() as isize)
+    }
+
     pub const fn dist_city(&self, other: Position) -> usize {
         self.col().abs_diff(other.col()) + self.row().abs_diff(other.row())
     }
@@ -97,8 +107,45 @@ impl TryFrom<char> for Square {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Maze {
+    Small,
+    Regular,
+    Pink,
+    LightBlue,
+    Orange,
+    DarkBlue,
+    Vcs,
+}
+
+impl Maze {
+    #[cfg(test)]
+    pub const ALL: [Maze; 7] = [
+        Maze::Small,
+        Maze::Regular,
+        Maze::Pink,
+        Maze::LightBlue,
+        Maze::Orange,
+        Maze::DarkBlue,
+        Maze::Vcs,
+    ];
+
+    fn layout(&self) -> (&'static [&'static str], &'static str) {
+        match self {
+            Maze::Small => (&MAZE_SMALL_PACMAN, "Small"),
+            Maze::Regular => (&MAZE_REG_PACMAN, "Regular"),
+            Maze::Pink => (&MAZE_MS_PACMAN_PINK, "Pink"),
+            Maze::LightBlue => (&MAZE_MS_PACMAN_LIGHT_BLUE, "Light Blue"),
+            Maze::Orange => (&MAZE_MS_PACMAN_ORANGE, "Orange"),
+            Maze::DarkBlue => (&MAZE_MS_PACMAN_DARK_BLUE, "Dark Blue"),
+            Maze::Vcs => (&MAZE_VCS_PACMAN, "VCS"),
+        }
+    }
+}
+
 pub struct Board {
     board: Vec<Square>,
+    pub maze: Maze,
     pub maze_name: &'static str,
     pub width: usize,
     pub height: usize,
@@ -109,18 +156,14 @@ pub struct Board {
     pub fruit: Position,
     pub pacman_start: Position,
     pub ghost_start: [Position; 4],
+    pub home: DistanceField, // path lengths to the ghost house gates - for ghost eyes
+    pub paths: DistanceTable,
+    pub junctions: Junctions,
 }
 
 impl Board {
-    pub fn new(level: u32) -> Self {
-        let (maze, maze_name): (&[&str], &str) = match level {
-            0 => (&MAZE_SMALL_PACMAN, "Small"),
-            2 => (&MAZE_MS_PACMAN_PINK, "Pink"),
-            3 => (&MAZE_MS_PACMAN_LIGHT_BLUE, "Light Blue"),
-            4 => (&MAZE_MS_PACMAN_ORANGE, "Orange"),
-            5 => (&MAZE_MS_PACMAN_DARK_BLUE, "Dark Blue"),
-            _ => (&MAZE_REG_PACMAN, "Regular"),
-        };
+    pub fn new(which: Maze) -> Self {
+        let (maze, maze_name) = which.layout();
 
         let board: Vec<Square> = maze
             .iter()
@@ -185,8 +228,9 @@ impl Board {
             Position::from_xy(min_col, max_row),
             Position::from_xy(max_col, max_row),
         ];
-        Board {
+        let mut board = Board {
             board,
+            maze: which,
             maze_name,
             width,
             height,
@@ -197,12 +241,61 @@ impl Board {
             front_of_gate2: gate2.go(Up),
             fruit,
             ghost_start,
-        }
+            home: DistanceField::default(),
+            paths: DistanceTable::default(),
+            junctions: Junctions::default(),
+        };
+        board.home = DistanceField::new(&board, &[board.front_of_gate1, board.front_of_gate2]);
+        board.paths = DistanceTable::new(&board);
+        board.junctions = Junctions::new(&board);
+        board
     }
 
     pub fn dots(&self) -> usize {
         self.board.iter().filter(|&c| *c == Square::Dot).count()
     }
+
+    // the outer ends of the tunnels - where they wrap around
+    pub fn tunnel_ends(&self) -> Vec<Position> {
+        (0..self.height)
+            .flat_map(|row| {
+                [
+                    Position::from_xy(0, row),
+                    Position::from_xy(self.width - 1, row),
+                ]
+            })
+            .filter(|&p| self[p] == Square::Tunnel)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.board.len()
+    }
+
+    // can ghosts and pacman travel on this square
+    pub fn is_open(&self, p: Position) -> bool {
+        matches!(
+            self[p],
+            Square::Pill
+                | Square::Empty
+                | Square::Dot
+                | Square::Fruit
+                | Square::Tunnel
+                | Square::Start
+        )
+    }
+
+    // neighbouring squares, wrapping around horizontally - the top and bottom rows have none beyond
+    pub fn neighbours(&self, p: Position) -> impl Iterator<Item = (Direction, Position)> + '_ {
+        [Right, Left, Down, Up]
+            .into_iter()
+            .filter(move |&d| match d {
+                Up => p.row() > 0,
+                Down => p.row() + 1 < self.height,
+                _ => true,
+            })
+            .map(move |d| (d, p.go(d)))
+    }
 }
 
 impl Index<Position> for Board {
@@ -220,12 +313,14 @@ impl IndexMut<Position> for Board {
 
 #[cfg(test)]
 mod tests {
+    use super::Maze;
     use crate::*;
 
     #[test]
     fn test_eval() {
-        for i in 0..4 {
-            let _ = Board::new(i);
+        for maze in Maze::ALL {
+            let board = Board::new(maze);
+            assert!(!board.tunnel_ends().is_empty());
         }
     }
 }
@@ -0,0 +1,237 @@
+// Intermissions - the short animated acts played between some of the levels.
+//
+// An act is a list of moves: a sprite crossing (or standing on) the stage at a fixed speed for
+// a while. Where everything is at any time follows from the time since the act started, so the
+// whole act can be skipped, or drawn at any frame rate.
+
+use crate::board::Direction::{self, *};
+use crate::rules::TILE_MS;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Act {
+    Chase,    // Blinky chases pacman, then the giant pacman chases Blinky
+    Sheet,    // Blinky's sheet snags on a nail and tears
+    Patched,  // the patched up Blinky, then Blinky dragging his sheet along
+    TheyMeet, // Ms. Pac-Man acts
+    TheChase,
+    Junior,
+}
+
+impl Act {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Act::Chase => "ACT 1",
+            Act::Sheet => "ACT 2",
+            Act::Patched => "ACT 3",
+            Act::TheyMeet => "ACT 1 THEY MEET",
+            Act::TheChase => "ACT 2 THE CHASE",
+            Act::Junior => "ACT 3 JUNIOR",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sprite {
+    Pacman(Direction),
+    MsPacman(Direction),
+    Ghost(usize),
+    Scared,
+    Giant, // 3x3, the column is its left edge and the row its middle
+    Nail,
+    Sheet, // a piece of Blinky's sheet
+    Torn,  // Blinky with a torn sheet
+    Naked, // Blinky without his sheet
+    Heart,
+    Stork,
+    Junior,
+}
+
+// sprite at column from (row relative to the stage) moving at speed (% - negative is to the
+// left) from start until end (ms into the act)
+struct Move {
+    sprite: Sprite,
+    row: i32,
+    from: i32,
+    speed: i32,
+    start: u128,
+    end: u128,
+}
+
+pub struct Actor {
+    pub sprite: Sprite,
+    pub col: i32,
+    pub row: i32,
+}
+
+pub struct Cutscene {
+    pub act: Act,
+    pub elapsed: u128,
+    moves: Vec<Move>,
+}
+
+// time (ms) to cross a number of squares at speed (%)
+fn ms(squares: i32, speed: i32) -> u128 {
+    (squares * 100 * TILE_MS as i32 / speed) as u128
+}
+
+fn walk(sprite: Sprite, row: i32, from: i32, speed: i32, start: u128, end: u128) -> Move {
+    Move {
+        sprite,
+        row,
+        from,
+        speed,
+        start,
+        end,
+    }
+}
+
+fn stand(sprite: Sprite, row: i32, col: i32, start: u128, end: u128) -> Move {
+    walk(sprite, row, col, 0, start, end)
+}
+
+// the stage is width squares wide
+fn moves(act: Act, width: i32) -> Vec<Move> {
+    let (w, mid) = (width, width / 2);
+    match act {
+        Act::Chase => {
+            // Blinky is just a bit faster, but both leave the stage before he catches up
+            let t1 = ms(w + 8, 105);
+            let t2 = t1 + 500;
+            vec![
+                walk(Sprite::Pacman(Left), 0, w, -100, 0, ms(w + 2, 100)),
+                walk(Sprite::Ghost(1), 0, w + 4, -105, 0, t1),
+                walk(Sprite::Scared, 0, -2, 60, t2, t2 + ms(w + 4, 60)),
+                walk(Sprite::Giant, 0, -10, 70, t2, t2 + ms(w + 10, 70)),
+            ]
+        }
+        Act::Sheet => {
+            // Blinky snags on the nail, struggles for a while and the sheet tears
+            let caught = ms(w + 6 - mid, 100);
+            let torn = caught + 1500;
+            let end = torn + 2500;
+            vec![
+                stand(Sprite::Nail, 0, mid, 0, end),
+                walk(Sprite::Pacman(Left), 0, w, -100, 0, ms(w + 2, 100)),
+                walk(Sprite::Ghost(1), 0, w + 6, -100, 0, caught),
+                stand(Sprite::Ghost(1), 0, mid - 1, caught, torn),
+                stand(Sprite::Sheet, 0, mid + 1, caught, end),
+                stand(Sprite::Torn, 0, mid - 2, torn, end),
+            ]
+        }
+        Act::Patched => {
+            let t1 = ms(w + 7, 100);
+            let t2 = t1 + 500;
+            let end = t2 + ms(w + 6, 50);
+            vec![
+                walk(Sprite::Pacman(Left), 0, w, -100, 0, ms(w + 2, 100)),
+                walk(Sprite::Torn, 0, w + 5, -100, 0, t1),
+                walk(Sprite::Naked, 0, -2, 50, t2, end),
+                walk(Sprite::Sheet, 0, -4, 50, t2, end),
+            ]
+        }
+        Act::TheyMeet => {
+            // chased from both sides they meet in the middle, step aside and the ghosts crash
+            let meet = ms(mid + 1, 100);
+            let crash = meet + ms(5, 100);
+            let end = crash + 2500;
+            vec![
+                walk(Sprite::Pacman(Right), 0, -2, 100, 0, meet),
+                walk(Sprite::MsPacman(Left), 0, w + 2, -100, 0, meet),
+                walk(Sprite::Ghost(2), 0, -8, 100, 0, crash),
+                walk(Sprite::Ghost(0), 0, w + 8, -100, 0, crash),
+                stand(Sprite::Pacman(Right), -1, mid - 1, meet, end),
+                stand(Sprite::MsPacman(Left), -1, mid + 1, meet, end),
+                stand(Sprite::Heart, -2, mid - 1, crash, end),
+            ]
+        }
+        Act::TheChase => {
+            let t1 = ms(w + 6, 100);
+            let t2 = t1 + 500;
+            let end = t2 + ms(w + 6, 160);
+            vec![
+                walk(Sprite::MsPacman(Left), 0, w, -100, 0, ms(w + 2, 100)),
+                walk(Sprite::Pacman(Left), 0, w + 4, -100, 0, t1),
+                walk(Sprite::Pacman(Right), 0, -2, 160, t2, end),
+                walk(Sprite::MsPacman(Right), 0, -6, 160, t2, end),
+            ]
+        }
+        Act::Junior => {
+            // the stork drops junior between them
+            let drop = ms(w + 2 - mid, 60);
+            let gone = ms(w + 4, 60);
+            let end = gone.max(drop) + 2000;
+            vec![
+                stand(Sprite::Pacman(Right), 0, mid - 3, 0, end),
+                stand(Sprite::MsPacman(Left), 0, mid + 3, 0, end),
+                walk(Sprite::Stork, -2, w + 2, -60, 0, gone),
+                walk(Sprite::Junior, -1, w + 2, -60, 0, drop),
+                stand(Sprite::Junior, 0, mid, drop, end),
+            ]
+        }
+    }
+}
+
+impl Cutscene {
+    pub fn new(act: Act, width: i32) -> Self {
+        Cutscene {
+            act,
+            elapsed: 0,
+            moves: moves(act, width),
+        }
+    }
+
+    pub fn update(&mut self, telaps: u128) {
+        self.elapsed += telaps;
+    }
+
+    pub fn done(&self) -> bool {
+        self.moves.iter().all(|m| self.elapsed >= m.end)
+    }
+
+    // the sprites on the stage now - some may be (partly) off the stage
+    pub fn actors(&self) -> Vec<Actor> {
+        let t = self.elapsed;
+        self.moves
+            .iter()
+            .filter(|m| (m.start..m.end).contains(&t))
+            .map(|m| {
+                let travelled = (t - m.start) as i64 * m.speed as i64 / (100 * TILE_MS) as i64;
+                Actor {
+                    sprite: m.sprite,
+                    col: m.from + travelled as i32,
+                    row: m.row,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acts_leave_the_stage() {
+        let width = 28;
+        for act in [
+            Act::Chase,
+            Act::Sheet,
+            Act::Patched,
+            Act::TheyMeet,
+            Act::TheChase,
+            Act::Junior,
+        ] {
+            let mut scene = Cutscene::new(act, width);
+            assert!(!scene.actors().is_empty());
+            while !scene.done() {
+                // nobody wanders far off the stage
+                for a in scene.actors() {
+                    assert!((-12..width + 10).contains(&a.col), "{act:?} {:?}", a.sprite);
+                }
+                scene.update(50);
+            }
+            assert!(scene.actors().is_empty());
+            assert!(scene.elapsed < 15000, "{act:?}");
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// Pixel graphics in the terminal. Each cell shows two pixels, one above the other, with the
+// half block characters ▀ ▄ █ - the foreground colour for one pixel and the background for the
+// other. Terminal cells are about twice as tall as they are wide, so the pixels come out square.
+
+use crate::board::Direction;
+use crate::render::Renderer;
+use crossterm::style::{Color, ContentStyle, Stylize};
+use std::io;
+
+pub struct Canvas {
+    pub width: i32,
+    pub height: i32,
+    px: Vec<Option<Color>>, // None - the terminal's background
+}
+
+impl Canvas {
+    pub fn new(width: i32, height: i32) -> Self {
+        Canvas {
+            width,
+            height,
+            px: vec![None; (width * height) as usize],
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<Color> {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            self.px[(y * self.width + x) as usize]
+        } else {
+            None
+        }
+    }
+
+    // pixels off the canvas are dropped - sprites may be partly outside
+    pub fn set(&mut self, x: i32, y: i32, c: Color) {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            self.px[(y * self.width + x) as usize] = Some(c);
+        }
+    }
+
+    pub fn fill(&mut self, x: i32, y: i32, w: i32, h: i32, c: Color) {
+        for y in y..y + h {
+            for x in x..x + w {
+                self.set(x, y, c);
+            }
+        }
+    }
+
+    // the whole canvas - blank pixels too, so it covers what was drawn there before
+    pub fn draw(&self, r: &mut dyn Renderer, col: u16, row: u16) -> io::Result<()> {
+        for y in 0..(self.height + 1) / 2 {
+            for x in 0..self.width {
+                let (s, style) = match (self.get(x, 2 * y), self.get(x, 2 * y + 1)) {
+                    (None, None) => (" ", ContentStyle::new()),
+                    (Some(top), None) => ("▀", ContentStyle::new().with(top)),
+                    (None, Some(bottom)) => ("▄", ContentStyle::new().with(bottom)),
+                    (Some(top), Some(bottom)) if top == bottom => {
+                        ("█", ContentStyle::new().with(top))
+                    }
+                    (Some(top), Some(bottom)) => ("▀", ContentStyle::new().with(top).on(bottom)),
+                };
+                r.print(col + x as u16, row + y as u16, s, style)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// the pixels within radius of (cx, cy) - pixel (x, y) covers x..x+1, y..y+1
+fn in_disc(x: i32, y: i32, cx: f32, cy: f32, radius: f32) -> bool {
+    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+    dx * dx + dy * dy <= radius * radius
+}
+
+pub fn disc(c: &mut Canvas, cx: f32, cy: f32, radius: f32, color: Color) {
+    let r = radius.ceil() as i32;
+    for y in cy as i32 - r..=cy as i32 + r {
+        for x in cx as i32 - r..=cx as i32 + r {
+            if in_disc(x, y, cx, cy, radius) {
+                c.set(x, y, color);
+            }
+        }
+    }
+}
+
+const YELLOW: Color = Color::Rgb {
+    r: 255,
+    g: 255,
+    b: 0,
+};
+
+// pacman - a disc with a wedge cut out of it, mouth degrees wide, facing the way he moves.
+// At 360 degrees there is nothing left of him
+pub fn pacman(c: &mut Canvas, cx: f32, cy: f32, radius: f32, facing: Direction, mouth: f32) {
+    if mouth >= 360.0 {
+        return;
+    }
+    let (fx, fy) = match facing {
+        Direction::Left => (-1.0, 0.0),
+        Direction::Right => (1.0, 0.0),
+        Direction::Up => (0.0, -1.0),
+        Direction::Down => (0.0, 1.0),
+    };
+    let r = radius.ceil() as i32;
+    for y in cy as i32 - r..=cy as i32 + r {
+        for x in cx as i32 - r..=cx as i32 + r {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            // the pixel in the very centre has no angle - it is never in the mouth
+            let h = dx.hypot(dy);
+            let in_mouth = mouth > 0.0
+                && h > 0.0
+                && ((dx * fx + dy * fy) / h).acos().to_degrees() <= mouth / 2.0;
+            if in_disc(x, y, cx, cy, radius) && !in_mouth {
+                c.set(x, y, YELLOW);
+            }
+        }
+    }
+}
+
+// 6x6, with two frames for the wavy skirt. b - body, e - eye, p - pupil
+#[rustfmt::skip]
+const GHOST: [[&str; 6]; 2] = [
+    [".bbbb.", "bebbeb", "bpbbpb", "bbbbbb", "bbbbbb", "b.bb.b"],
+    [".bbbb.", "bebbeb", "bpbbpb", "bbbbbb", "bbbbbb", "bb..bb"],
+];
+
+// a ghost with its top left corner at (x, y). Without a body only the eyes are drawn
+pub fn ghost(
+    c: &mut Canvas,
+    x: i32,
+    y: i32,
+    body: Option<Color>,
+    eyes: (Color, Color),
+    frame: usize,
+) {
+    for (dy, line) in GHOST[frame % 2].iter().enumerate() {
+        for (dx, p) in line.chars().enumerate() {
+            let color = match p {
+                'b' => body,
+                'e' => Some(eyes.0),
+                'p' => Some(eyes.1),
+                _ => None,
+            };
+            if let Some(color) = color {
+                c.set(x + dx as i32, y + dy as i32, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::TextGrid;
+
+    #[test]
+    fn test_half_blocks() {
+        let mut c = Canvas::new(4, 3);
+        c.set(0, 0, Color::Red);
+        c.set(1, 1, Color::Red);
+        c.fill(2, 0, 1, 2, Color::Red);
+        c.set(3, 0, Color::Red);
+        c.set(3, 1, Color::Blue);
+        c.set(0, 2, Color::Red);
+        let mut grid = TextGrid::new(4, 2);
+        c.draw(&mut grid, 0, 0).unwrap();
+        assert_eq!(grid.row(0), "▀▄█▀");
+        assert_eq!(grid.row(1), "▀   ");
+    }
+
+    #[test]
+    fn test_pacman_mouth() {
+        let count = |mouth| {
+            let mut c = Canvas::new(8, 8);
+            pacman(&mut c, 4.0, 4.0, 3.0, Direction::Right, mouth);
+            c.px.iter().flatten().count()
+        };
+        let closed = count(0.0);
+        assert_eq!(closed, 32);
+        assert!(count(90.0) < closed);
+        assert_eq!(count(360.0), 0);
+        // the mouth is on the side he faces
+        let mut c = Canvas::new(8, 8);
+        pacman(&mut c, 4.0, 4.0, 3.0, Direction::Right, 90.0);
+        assert!(c.get(6, 4).is_none() && c.get(1, 4).is_some());
+    }
+
+    #[test]
+    fn test_pacman_between_pixels() {
+        // centred on a pixel, with a row of pixels straight ahead of him
+        let (cx, cy) = (4.5, 4.5);
+        let mut round = Canvas::new(10, 10);
+        disc(&mut round, cx, cy, 3.0, YELLOW);
+        let mut c = Canvas::new(10, 10);
+        pacman(&mut c, cx, cy, 3.0, Direction::Right, 0.0);
+        assert!(c.px == round.px);
+        let mut c = Canvas::new(10, 10);
+        pacman(&mut c, cx, cy, 3.0, Direction::Right, 90.0);
+        assert!(c.get(4, 4).is_some() && c.get(6, 4).is_none());
+    }
+}
@@ -1,12 +1,22 @@
-use std::io::{self, Write, stdout};
+use std::collections::VecDeque;
+use std::io;
 use std::{thread, time, time::Duration};
 
 mod audio;
 mod board;
+mod cutscene;
+mod halfblock;
 mod maze;
+mod path;
+mod render;
+mod rules;
+mod synth;
 mod tui;
-use audio::{AM, Sound};
+use audio::{AM, Channel, Siren, Sound, SoundSet};
 use board::{Board, Direction, Direction::*, Position, Square};
+use cutscene::{Act, Cutscene};
+use render::Renderer;
+use rules::Ruleset;
 
 static MARQUEE: &str = "Title: A Dialogue Between Plato and Socrates on Pac-Man. \
     Scene: A quiet garden in Athens. Plato and Socrates sit on a stone bench, discussing the nature of games. \
@@ -28,6 +38,10 @@ static MARQUEE: &str = "Title: A Dialogue Between Plato and Socrates on Pac-Man.
     Fin.";
 
 const MAX_PACMAN_LIVES: u32 = 6;
+const FRAME_MS: u64 = 50;
+const MARQUEE_MS: u64 = 130;
+const GHOST_FREEZE_MS: u128 = 1000;
+const FRUIT_POPUP_MS: u128 = 2000;
 fn pct(n: u8) -> bool {
     rand::random_range(0..100) < n
 }
@@ -41,61 +55,127 @@ enum GameState {
 #[derive(PartialEq, Clone, Copy)]
 enum GhostState {
     Home,
+    Leaving,
     Gateway,
     Outside,
     Dead,
 }
 
+#[derive(Debug, PartialEq)]
+enum Contact {
+    Miss,
+    Caught, // pacman dies
+    Eaten,
+}
+
 #[derive(Clone, Copy)]
 struct Ghost {
     pos: Position,
+    home: Position,
     state: GhostState,
     edible_duration: u128,
     direction: Direction,
+    reverse: bool,  // arcade - turn around at the next move
+    progress: u32,  // fraction of the way to the next square
+    prev: Position, // where the last move started - the ghost is drawn on its way from there
 }
 
 impl Ghost {
     const fn new(pos: Position) -> Self {
         Ghost {
             pos,
+            home: pos,
             direction: Left,
             edible_duration: 0,
             state: GhostState::Home,
+            reverse: false,
+            progress: 0,
+            prev: pos,
         }
     }
 
-    fn slow(&self, level: u32, in_tunnel: bool) -> bool {
-        match level {
-            0 if in_tunnel => pct(60),
-            0 if self.edible_duration > 0 => pct(60),
-            0 => pct(25),
-            1..=3 if in_tunnel => pct(55),
-            1..=3 if self.edible_duration > 0 => pct(50),
-            1..=3 => pct(15),
-            _ if in_tunnel => pct(50),
-            _ if self.edible_duration > 0 => pct(45),
-            _ => pct(5),
+    // Did pacman (now at pos) meet this ghost anywhere along the trail of squares it has
+    // occupied this frame (starting where it was)? Also catches pacman and a ghost passing
+    // each other head on, which comparing positions only would miss
+    fn contact(&self, trail: &[Position], pos: Position) -> Contact {
+        if self.state == GhostState::Dead || !trail.contains(&pos) {
+            Contact::Miss
+        } else if self.edible_duration == 0 {
+            Contact::Caught
+        } else {
+            Contact::Eaten
+        }
+    }
+
+    // arcade ghost house: bob up and down while waiting
+    fn bob(&self, board: &Board) -> (Direction, Position) {
+        let away = if board[self.home.go(Up)] == Square::House {
+            Up
+        } else {
+            Down
+        };
+        if self.pos == self.home {
+            (away, self.home.go(away))
+        } else {
+            (away.opposite(), self.home)
+        }
+    }
+
+    // arcade ghost house: line up under the nearest gate and go out
+    fn leave(&mut self, board: &Board) -> (Direction, Position) {
+        let gate = if self.pos.col().abs_diff(board.gate1.col())
+            <= self.pos.col().abs_diff(board.gate2.col())
+        {
+            board.gate1
+        } else {
+            board.gate2
+        };
+        let d = match self.pos.col().cmp(&gate.col()) {
+            std::cmp::Ordering::Less => Right,
+            std::cmp::Ordering::Greater => Left,
+            std::cmp::Ordering::Equal => Up,
+        };
+        let pos = self.pos.go(d);
+        if board[pos] == Square::Gate {
+            self.state = GhostState::Gateway;
         }
+        (d, pos)
     }
 
-    fn moves(&self, board: &Board, target: Position) -> (Direction, Position) {
+    // arcade frightened ghosts pick a pseudo random turn at junctions - if that way is blocked
+    // they try Up, Left, Down and Right in that order
+    fn wander(&self, board: &Board, rnd: usize) -> (Direction, Position) {
+        let dirs = [Up, Left, Down, Right];
+        let first = if board.junctions.is_junction(self.pos) {
+            Some(dirs[rnd % 4])
+        } else {
+            None // just follow the corridor
+        };
+        first
+            .into_iter()
+            .chain(dirs)
+            .filter(|&d| d != self.direction.opposite())
+            .map(|d| (d, self.pos.go(d)))
+            .find(|&(_, p)| board.is_open(p))
+            .unwrap_or((self.direction, self.pos))
+    }
+
+    // classic rules go by the city block distance to the target, the VCS rules by path length
+    fn moves(&self, board: &Board, target: Position, by_path: bool) -> (Direction, Position) {
         [Right, Left, Down, Up]
             .into_iter()
             .filter_map(|d| {
                 let p = self.pos.go(d);
 
                 // never go back unless fleeing pacman
-                if matches!(
-                    board[p],
-                    Square::Pill
-                        | Square::Empty
-                        | Square::Dot
-                        | Square::Fruit
-                        | Square::Tunnel
-                        | Square::Start
-                ) && (self.edible_duration > 0 || d != self.direction.opposite())
+                if board.is_open(p) && (self.edible_duration > 0 || d != self.direction.opposite())
                 {
-                    Some((target.dist_city(p) as isize, d, p))
+                    // targets may be off the maze - no path length for those
+                    let dst = match board.paths.get(target, p) {
+                        Some(dst) if by_path => usize::from(dst),
+                        _ => target.dist_city(p),
+                    };
+                    Some((dst as isize, d, p))
                 } else {
                     None
                 }
@@ -108,33 +188,107 @@ impl Ghost {
             .map(|(_, dir, pos)| (dir, pos))
             .unwrap_or((self.direction, self.pos)) // Default to stay in place if no move is possible - never happens
     }
+
+    // arcade choice: the square nearest (in a straight line) to the target, never reversing,
+    // with ties broken in the order Up, Left, Down, Right
+    fn aim(&self, board: &Board, target: (isize, isize)) -> (Direction, Position) {
+        [Up, Left, Down, Right]
+            .into_iter()
+            .filter(|&d| d != self.direction.opposite())
+            .map(|d| (d, self.pos.go(d)))
+            .filter(|&(_, p)| board.is_open(p))
+            .min_by_key(|&(_, p)| {
+                let (col, row) = p.tile();
+                (col - target.0).pow(2) + (row - target.1).pow(2)
+            })
+            .unwrap_or((self.direction, self.pos))
+    }
 }
 
 struct Player {
     pos: Position,
     dead: bool,
-    last_input_direction: Direction,
+    turns: VecDeque<Direction>, // turns asked for, but not taken yet
     moving: Direction,
     anim_frame: usize,
-    timecum: u128, // for animation
+    anim_px: u32,       // pixels moved since the last animation frame
+    offset: (i32, i32), // pixels from the centre of pos (-4..=3) - sub square position
+    progress: u32,      // fraction of the way to the next pixel
+    eating: bool,       // ate a dot at the last step - moves slower
 }
 
+// pacman may turn this many pixels before or after the centre of a square
+const TURN_PX: i32 = 3;
+// pixels per frame of the mouth animation
+const ANIM_PX: u32 = 5;
+const TURN_QUEUE: usize = 2;
+
 impl Player {
+    // pixels from the centre of pos in direction d
+    const fn ahead(&self, d: Direction) -> i32 {
+        match d {
+            Right => self.offset.0,
+            Left => -self.offset.0,
+            Down => self.offset.1,
+            Up => -self.offset.1,
+        }
+    }
+
+    // pre-select a turn - only the latest few are remembered
+    fn queue_turn(&mut self, d: Direction) {
+        if self.turns.back() == Some(&d) {
+            return;
+        }
+        if self.turns.len() == TURN_QUEUE {
+            self.turns.pop_front();
+        }
+        self.turns.push_back(d);
+    }
+
+    fn can_turn(&self, board: &Board, d: Direction) -> bool {
+        d == self.moving.opposite()
+            || (d != self.moving
+                && self.ahead(self.moving).abs() <= TURN_PX
+                && board.is_open(self.pos.go(d)))
+    }
+
     fn new(pacman_start: Position) -> Player {
         Player {
             pos: pacman_start,
             dead: false,
-            last_input_direction: Left,
+            turns: VecDeque::new(),
             moving: Left,
             anim_frame: 0,
-            timecum: 0,
+            anim_px: 0,
+            offset: (0, 0),
+            progress: 0,
+            eating: false,
         }
     }
 }
 
+// the bonus fruit while it is on the board - moving along its route, or sitting still
+// for a given duration (ms)
+struct BonusFruit {
+    pos: Position,
+    direction: Direction,
+    progress: u32,
+    route: VecDeque<Position>, // leaves the board at the last one
+    duration: Option<u128>,
+}
+
+// score shown on the board for a while
+struct Popup {
+    pos: Position,
+    text: String,
+    duration: u128,
+}
+
 struct Game {
+    rules: Ruleset,
     board: Board,
     mq_idx: usize,
+    layout: tui::Layout,
     timecum: u128, // time is divided into Chase/Scatter Periods
     dots_left: u32,
     high_score: u32,
@@ -142,10 +296,20 @@ struct Game {
     player: Player,
     level: u32,
     ghosts: [Ghost; 4],
-    pill_duration: u128,
-    fruit_duration: u128,
+    fruit: Fruit,
+    bonus_fruit: Option<BonusFruit>,
+    fruits: usize, // released this level
+    dots_eaten: u32,
     next_ghost_score: u32,
     score: u32,
+    dot_counters: [u32; 4], // arcade ghost house release
+    global_dots: Option<u32>,
+    elroy_suspended: bool, // after losing a life, until Clyde leaves the house
+    since_dot: u128,
+    popups: Vec<Popup>,
+    freeze: u128, // the board stands still after a ghost is eaten
+    last_period: Period,
+    seed: usize, // pseudo random numbers for frightened ghosts - same sequence every level/life
     am: AM,
 }
 
@@ -165,50 +329,92 @@ enum Fruit {
     Galaxian,
     Bell,
     Key,
+    // Ms. Pac-Man
+    Orange,
+    Pretzel,
+    Pear,
+    Banana,
+    // Atari VCS
+    Vitamin,
 }
 
 impl Fruit {
-    pub fn value(&self) -> u32 {
+    const MS_PACMAN: [Fruit; 7] = [
+        Fruit::Cherries,
+        Fruit::Strawberry,
+        Fruit::Orange,
+        Fruit::Pretzel,
+        Fruit::RedApple,
+        Fruit::Pear,
+        Fruit::Banana,
+    ];
+
+    pub fn value(&self, rules: Ruleset) -> u32 {
         match self {
-            Self::Cherries => 100,
+            Self::Cherries | Self::Vitamin => 100,
+            Self::Strawberry if rules == Ruleset::MsPacman => 200,
             Self::Strawberry => 300,
-            Self::Peach => 500,
-            Self::RedApple => 700,
+            Self::Peach | Self::Orange => 500,
+            Self::RedApple if rules == Ruleset::MsPacman => 1000,
+            Self::RedApple | Self::Pretzel => 700,
             Self::Grapes => 1000,
-            Self::Galaxian => 2000,
+            Self::Galaxian | Self::Pear => 2000,
             Self::Bell => 3000,
-            Self::Key => 5000,
+            Self::Key | Self::Banana => 5000,
         }
     }
 }
 
 impl Game {
-    fn new() -> Self {
+    fn new(rules: Ruleset, am: AM) -> Self {
         let level = 0u32;
-        let board = Board::new(level);
+        let board = Board::new(rules.maze(level));
         let player = Player::new(board.pacman_start);
         let mut game = Game {
+            rules,
             timecum: 0,
             mq_idx: 0,
+            layout: tui::Layout::Narrow,
             ghosts: [Ghost::new(Position::from_xy(0, 0)); 4],
-            pill_duration: 6000,
             level,
             board,
             dots_left: 0,
             high_score: 9710,
             lives: 3,
             player,
-            fruit_duration: 0,
+            bonus_fruit: None,
+            fruit: Fruit::Cherries,
+            fruits: 0,
+            dots_eaten: 0,
             next_ghost_score: 0,
             score: 0,
-            am: AM::default(),
+            dot_counters: [0; 4],
+            global_dots: None,
+            elroy_suspended: false,
+            since_dot: 0,
+            popups: Vec::new(),
+            freeze: 0,
+            last_period: Period::Scatter,
+            seed: 0,
+            am,
         };
         game.reset_ghosts();
         game.repopulate_board();
         game
     }
 
+    // the bonus fruit - Ms. Pac-Man picks one at random after level 7, the VCS game only has
+    // vitamins
     fn bonus(&self) -> Fruit {
+        if self.rules == Ruleset::Vcs {
+            return Fruit::Vitamin;
+        }
+        if self.rules == Ruleset::MsPacman {
+            return match self.level {
+                0..=6 => Fruit::MS_PACMAN[self.level as usize],
+                _ => Fruit::MS_PACMAN[rand::random_range(0..Fruit::MS_PACMAN.len())],
+            };
+        }
         match self.level {
             0 => Fruit::Cherries,
             1 => Fruit::Strawberry,
@@ -221,8 +427,134 @@ impl Game {
         }
     }
 
+    // arcade Cruise Elroy stage (0: off) - Blinky speeds up towards the end of a level.
+    // Suspended after a life is lost until Clyde leaves the house
+    fn elroy(&self) -> u8 {
+        if !self.rules.arcade() || self.elroy_suspended {
+            return 0;
+        }
+        let limit = rules::elroy_dots(self.level);
+        if self.dots_left <= limit / 2 {
+            2
+        } else if self.dots_left <= limit {
+            1
+        } else {
+            0
+        }
+    }
+
     fn reset_ghosts(&mut self) {
         self.ghosts = self.board.ghost_start.map(Ghost::new);
+        if self.rules.arcade() {
+            // Blinky starts outside
+            self.ghosts[1].pos = self.board.front_of_gate1;
+            self.ghosts[1].state = GhostState::Outside;
+            // ghosts in the bottom row start out of phase, so bobbing ghosts never meet
+            for g in self.ghosts[2..].iter_mut() {
+                (_, g.pos) = g.bob(&self.board);
+            }
+        }
+        for g in self.ghosts.iter_mut() {
+            g.prev = g.pos;
+        }
+    }
+
+    // arcade ghost house: the first ghost (in release order) still inside may leave
+    // once enough dots have been eaten, or if pacman has stopped eating for a while
+    fn release_ghosts(&mut self, telaps: u128) {
+        self.since_dot += telaps;
+        let Some(gidx) = rules::RELEASE_ORDER
+            .into_iter()
+            .find(|&i| self.ghosts[i].state == GhostState::Home)
+        else {
+            return;
+        };
+        let release = match self.global_dots {
+            Some(n) if n >= rules::GLOBAL_DOT_OFF => {
+                self.global_dots = None; // back to per ghost counters
+                false
+            }
+            Some(n) => rules::global_dot_limit(gidx).is_some_and(|limit| n >= limit),
+            None => self.dot_counters[gidx] >= rules::dot_limit(self.level, gidx),
+        };
+        if release || self.since_dot >= rules::release_timer(self.level) {
+            self.since_dot = 0;
+            self.ghosts[gidx].state = GhostState::Leaving;
+            if gidx == 3 {
+                self.elroy_suspended = false;
+            }
+        }
+    }
+
+    // the bonus fruit appears after a set number of dots
+    fn fruit_due(&self) -> bool {
+        match self.rules {
+            Ruleset::MsPacman => self.fruits < 2 && self.dots_eaten >= [64, 176][self.fruits],
+            _ => matches!(self.dots_left, 74 | 174),
+        }
+    }
+
+    // Ms. Pac-Man's fruit comes in through one of the tunnels, wanders by way of random
+    // junctions and below the ghost house, and leaves through another tunnel. Otherwise it
+    // sits below the ghost house for a while
+    fn release_fruit(&mut self) {
+        self.dots_left -= 1;
+        self.fruits += 1;
+        self.fruit = self.bonus();
+        let pick = |v: &[Position]| v[rand::random_range(0..v.len())];
+        self.bonus_fruit = Some(match self.rules {
+            Ruleset::MsPacman => {
+                let ends = self.board.tunnel_ends();
+                let entry = pick(&ends);
+                // another tunnel if there is one, else the other end of this one
+                let exit = ends
+                    .iter()
+                    .copied()
+                    .filter(|&p| p != entry)
+                    .max_by_key(|&p| (p.row() != entry.row(), rand::random::<u32>()))
+                    .unwrap_or(entry);
+                let junctions: Vec<Position> = (0..self.board.len())
+                    .map(|i| Position::from_xy(i % self.board.width, i / self.board.width))
+                    .filter(|&p| self.board.junctions.is_junction(p))
+                    .filter(|&p| self.board.paths.get(entry, p).is_some())
+                    .collect();
+                BonusFruit {
+                    pos: entry,
+                    direction: if entry.col() == 0 { Right } else { Left },
+                    progress: 0,
+                    route: VecDeque::from([
+                        pick(&junctions),
+                        self.board.fruit,
+                        pick(&junctions),
+                        exit,
+                    ]),
+                    duration: None,
+                }
+            }
+            _ => BonusFruit {
+                pos: self.board.fruit,
+                direction: Left,
+                progress: 0,
+                route: VecDeque::new(),
+                duration: Some(1000 * (10 + rand::random_range(0..3))),
+            },
+        });
+    }
+
+    fn dot_eaten(&mut self) {
+        self.dots_eaten += 1;
+        self.since_dot = 0;
+        match self.global_dots.as_mut() {
+            Some(n) => *n += 1,
+            None => {
+                if let Some(gidx) = rules::RELEASE_ORDER
+                    .into_iter()
+                    .find(|&i| self.ghosts[i].state == GhostState::Home)
+                {
+                    self.dot_counters[gidx] += 1;
+                }
+            }
+        }
     }
 
     fn period(&self) -> Period {
@@ -239,12 +571,38 @@ impl Game {
     }
 
     fn repopulate_board(&mut self) {
-        self.board = Board::new(self.level);
+        self.board = Board::new(self.rules.maze(self.level));
         self.dots_left = self.board.dots() as u32;
         self.dots_left += 2; // +2 pseudo dots for fruit bonuses
+        self.dots_eaten = 0;
+        self.fruits = 0;
+        self.fruit = self.bonus();
+        self.bonus_fruit = None;
+        self.dot_counters = [0; 4];
+        self.global_dots = None;
+        self.elroy_suspended = false;
+        self.since_dot = 0;
+        self.seed = 0;
+    }
+
+    fn random(&mut self) -> usize {
+        self.seed = (self.seed * 5 + 1) % 8192;
+        self.seed >> 3
+    }
+
+    // arcade ghosts outside the house turn around when the period changes or a pill is eaten
+    fn reverse_ghosts(&mut self) {
+        for g in self.ghosts.iter_mut() {
+            if g.state == GhostState::Outside {
+                g.reverse = true;
+            }
+        }
     }
 
     fn ghosts_are_edible(&mut self, duration: u128) {
+        if self.rules.arcade() {
+            self.reverse_ghosts();
+        }
         for g in self.ghosts.iter_mut() {
             if matches!(g.state, GhostState::Outside | GhostState::Gateway) {
                 g.edible_duration += duration
@@ -252,37 +610,102 @@ impl Game {
         }
     }
 
-    fn check_player_vs_ghosts(&mut self) -> io::Result<()> {
-        for gidx in 0..self.ghosts.len() {
+    fn check_player_vs_ghosts(&mut self, trails: &[Vec<Position>; 4]) -> io::Result<()> {
+        for (gidx, trail) in trails.iter().enumerate() {
             let g = &mut self.ghosts[gidx];
-            if g.state != GhostState::Dead && g.pos == self.player.pos {
-                if g.edible_duration == 0 {
+            match g.contact(trail, self.player.pos) {
+                Contact::Miss => (),
+                Contact::Caught => {
                     self.player.dead = true;
                     break;
-                } else {
+                }
+                Contact::Eaten => {
                     let score = self.next_ghost_score;
                     self.score += score;
                     self.next_ghost_score *= 2;
                     g.state = GhostState::Dead;
                     g.edible_duration = 0;
-                    self.am.play(Sound::EatGhost)?;
-                    {
-                        let mut w = io::BufWriter::new(stdout());
-                        tui::draw_message_at(&mut w, self, self.player.pos, &format!("{score}"))?;
-                    }
-                    thread::sleep(time::Duration::from_millis(150));
+                    self.am
+                        .play_at(Sound::EatGhost, audio::pan(g.pos.col(), self.board.width))?;
+                    // show the score where the ghost was eaten - everything else waits
+                    self.popup(self.player.pos, score, GHOST_FREEZE_MS);
+                    self.freeze = GHOST_FREEZE_MS;
                 }
             }
         }
         Ok(())
     }
 
-    fn update_fruit(&mut self, telaps: u128) {
+    // eyes going home drown the fright sound, which drowns the siren
+    fn siren(&self) -> Siren {
+        if self.ghosts.iter().any(|g| g.state == GhostState::Dead) {
+            Siren::Eyes
+        } else if self.ghosts.iter().any(|g| g.edible_duration > 0) {
+            Siren::Fright
+        } else {
+            let eaten = self.dots_eaten * audio::SIREN_STEPS / (self.dots_eaten + self.dots_left);
+            Siren::Normal(eaten.min(audio::SIREN_STEPS - 1))
+        }
+    }
+
+    // where the ghosts are out in the maze, for the ghost cues
+    fn ghost_cues(&self) -> [Option<f32>; 4] {
+        self.ghosts.each_ref().map(|g| {
+            matches!(g.state, GhostState::Outside | GhostState::Gateway)
+                .then(|| audio::pan(g.pos.col(), self.board.width))
+        })
+    }
+
+    // returns the squares the fruit occupied during the update
+    fn update_fruit(&mut self, telaps: u128) -> Vec<Position> {
         self.timecum += telaps;
-        self.fruit_duration = self.fruit_duration.saturating_sub(telaps);
+        let Some(f) = self.bonus_fruit.as_mut() else {
+            return Vec::new();
+        };
+        let mut trail = vec![f.pos];
+        if let Some(duration) = f.duration.as_mut() {
+            *duration = duration.saturating_sub(telaps);
+            if *duration == 0 {
+                self.bonus_fruit = None;
+            }
+            return trail;
+        }
+        for _ in 0..rules::steps(&mut f.progress, rules::FRUIT_SPEED, telaps, 1) {
+            while f.route.front() == Some(&f.pos) {
+                f.route.pop_front();
+            }
+            let Some(&target) = f.route.front() else {
+                self.bonus_fruit = None; // gone through the exit tunnel
+                break;
+            };
+            let Some(step) = self
+                .board
+                .paths
+                .step(&self.board, f.pos, f.direction, target)
+            else {
+                self.bonus_fruit = None; // no way there - its run is over
+                break;
+            };
+            (f.direction, f.pos) = step;
+            trail.push(f.pos);
+        }
+        trail
     }
 
-    fn update_ghosts(&mut self, telaps: u128) {
+    fn check_player_vs_fruit(&mut self, trail: &[Position]) -> io::Result<()> {
+        if self.bonus_fruit.is_some() && trail.contains(&self.player.pos) {
+            self.bonus_fruit = None;
+            let pan = audio::pan(self.player.pos.col(), self.board.width);
+            self.am.play_at(Sound::EatPill, pan)?;
+            let bonus = self.fruit.value(self.rules);
+            self.score += bonus;
+            self.popup(self.player.pos, bonus, FRUIT_POPUP_MS);
+        }
+        Ok(())
+    }
+
+    // scatter and chase targets - UniPac rules
+    fn classic_targets(&self) -> ([Position; 4], [Position; 4]) {
         let scatter_target: [Position; 4] = [
             Position::from_xy(2, 0),
             Position::from_xy(self.board.width - 3, 0),
@@ -308,93 +731,193 @@ impl Game {
         if self.player.pos.dist_sqr(self.ghosts[3].pos) >= 64 {
             chase_target[3] = scatter_target[3]
         }
+        (scatter_target, chase_target)
+    }
+
+    // arcade targets for Pinky, Blinky, Inky & Clyde - may be outside the maze
+    fn arcade_targets(&self, period: Period) -> [(isize, isize); 4] {
+        let (w, h) = (self.board.width as isize, self.board.height as isize);
+        // corners just beyond the maze, so ghosts circle the nearest block
+        let scatter = [(2, -3), (w - 3, -3), (w - 1, h + 1), (0, h + 1)];
+        if period == Period::Scatter {
+            return scatter;
+        }
 
+        let (col, row) = self.player.pos.tile();
+        let ahead = |n: isize| match self.player.moving {
+            Left => (col - n, row),
+            Right => (col + n, row),
+            Down => (col, row + n),
+            Up => (col - n, row - n), // overflow bug in the arcade - up is also n to the left
+        };
+        let blinky = self.ghosts[1].pos.tile();
+        let pivot = ahead(2);
+        [
+            // Pinky - 4 squares ahead of pacman
+            ahead(4),
+            // Blinky - pacman
+            (col, row),
+            // Inky - double the vector from Blinky to 2 squares ahead of pacman
+            (2 * pivot.0 - blinky.0, 2 * pivot.1 - blinky.1),
+            // Clyde - pacman when 8 or more squares away - otherwise his corner
+            if self.player.pos.dist_sqr(self.ghosts[3].pos) >= 64 {
+                (col, row)
+            } else {
+                scatter[3]
+            },
+        ]
+    }
+
+    // returns the squares each ghost occupied during the update
+    fn update_ghosts(&mut self, telaps: u128) -> [Vec<Position>; 4] {
+        let (scatter_target, chase_target) = self.classic_targets();
         let current_period = self.period();
+        let mut arcade_target = self.arcade_targets(current_period);
+        let elroy = self.elroy();
+        if elroy > 0 {
+            // Blinky as Cruise Elroy chases in the scatter periods too
+            arcade_target[1] = self.arcade_targets(Period::Chase)[1];
+        }
+        if self.rules.arcade() && current_period != self.last_period {
+            self.reverse_ghosts();
+        }
+        self.last_period = current_period;
+        let rnd: [usize; 4] = std::array::from_fn(|_| self.random());
+        let speeds = self.rules.speeds(self.level);
+        let ms_roam = self.rules == Ruleset::MsPacman && self.timecum < 7000;
+        let mut trails: [Vec<Position>; 4] = self.ghosts.map(|g| vec![g.pos]);
         for (gidx, g) in self.ghosts.iter_mut().enumerate() {
             g.edible_duration = g.edible_duration.saturating_sub(telaps);
-            (g.direction, g.pos) = match g.state {
-                GhostState::Home => {
-                    let pos = g
-                        .pos
-                        .go([Left, Right, Up, Down][rand::random_range(0..4)]);
-                    match self.board[pos] {
-                        Square::House => (Left, pos),
-                        Square::Gate => {
-                            g.state = GhostState::Gateway;
-                            (Left, pos)
+            let speed = match g.state {
+                GhostState::Dead => speeds.eyes,
+                GhostState::Home | GhostState::Leaving | GhostState::Gateway => speeds.ghost_tunnel,
+                GhostState::Outside if self.board[g.pos] == Square::Tunnel => speeds.ghost_tunnel,
+                GhostState::Outside if g.edible_duration > 0 => speeds.ghost_fright,
+                GhostState::Outside if gidx == 1 && elroy > 0 => speeds.elroy[elroy as usize - 1],
+                GhostState::Outside => speeds.ghost,
+            };
+            for _ in 0..rules::steps(&mut g.progress, speed, telaps, 1) {
+                g.prev = g.pos;
+                (g.direction, g.pos) = match g.state {
+                    GhostState::Home if self.rules.arcade() => g.bob(&self.board),
+                    GhostState::Leaving => g.leave(&self.board),
+                    GhostState::Home => {
+                        let pos = g.pos.go([Left, Right, Up, Down][rand::random_range(0..4)]);
+                        match self.board[pos] {
+                            Square::House => (Left, pos),
+                            Square::Gate => {
+                                g.state = GhostState::Gateway;
+                                (Left, pos)
+                            }
+                            _ => (g.direction, g.pos),
                         }
-                        _ => (g.direction, g.pos),
                     }
-                }
-                GhostState::Gateway => {
-                    g.state = GhostState::Outside;
-                    if pct(50) {
-                        (Left, g.pos.go(Up))
-                    } else {
-                        (Right, g.pos.go(Up))
-                    }
-                }
-                GhostState::Dead => {
-                    if g.pos == self.board.gate1 || g.pos == self.board.gate2 {
-                        g.state = GhostState::Home;
-                        (g.direction, g.pos.go(Down))
-                    } else if g.pos == self.board.front_of_gate1
-                        || g.pos == self.board.front_of_gate2
-                    {
-                        (Down, g.pos.go(Down))
-                    } else {
-                        g.moves(&self.board, self.board.front_of_gate1) // go home
+                    GhostState::Gateway => {
+                        g.state = GhostState::Outside;
+                        // arcade ghosts always head left
+                        if self.rules.arcade() || pct(50) {
+                            (Left, g.pos.go(Up))
+                        } else {
+                            (Right, g.pos.go(Up))
+                        }
                     }
-                }
-                GhostState::Outside => {
-                    if g.slow(self.level, self.board[g.pos] == Square::Tunnel) {
-                        continue;
+                    GhostState::Dead => {
+                        if g.pos == self.board.gate1 || g.pos == self.board.gate2 {
+                            // arcade ghosts are revived in the house and leave straight away
+                            g.state = if self.rules.arcade() {
+                                GhostState::Leaving
+                            } else {
+                                GhostState::Home
+                            };
+                            (g.direction, g.pos.go(Down))
+                        } else if g.pos == self.board.front_of_gate1
+                            || g.pos == self.board.front_of_gate2
+                        {
+                            (Down, g.pos.go(Down))
+                        } else {
+                            // go home - shortest path to the nearest gate
+                            self.board
+                                .home
+                                .step(&self.board, g.pos, g.direction)
+                                .unwrap_or((g.direction, g.pos))
+                        }
                     }
-                    match (g.edible_duration > 0, current_period) {
-                        (true, _) => g.moves(&self.board, self.player.pos),
-                        (false, Period::Chase) => g.moves(&self.board, chase_target[gidx]),
-                        (false, Period::Scatter) => g.moves(&self.board, scatter_target[gidx]),
+                    GhostState::Outside => {
+                        let by_path = self.rules == Ruleset::Vcs;
+                        let back = g.direction.opposite();
+                        if std::mem::take(&mut g.reverse) && self.board.is_open(g.pos.go(back)) {
+                            (back, g.pos.go(back))
+                        } else {
+                            match (g.edible_duration > 0, self.rules.arcade(), current_period) {
+                                (true, true, _) => g.wander(&self.board, rnd[gidx]),
+                                (true, false, _) => g.moves(&self.board, self.player.pos, by_path),
+                                // Ms. Pac-Man's first two ghosts roam at random in the first scatter period
+                                (false, true, _) if ms_roam && gidx < 2 => {
+                                    g.wander(&self.board, rnd[gidx])
+                                }
+                                (false, true, _) => g.aim(&self.board, arcade_target[gidx]),
+                                (false, false, Period::Chase) => {
+                                    g.moves(&self.board, chase_target[gidx], by_path)
+                                }
+                                (false, false, Period::Scatter) => {
+                                    g.moves(&self.board, scatter_target[gidx], by_path)
+                                }
+                            }
+                        }
                     }
-                }
-            } // match ghost_state
+                }; // match ghost_state
+                trails[gidx].push(g.pos);
+            }
         }
+        trails
+    }
+
+    fn popup(&mut self, pos: Position, score: u32, duration: u128) {
+        self.popups.push(Popup {
+            pos,
+            text: format!("{score}"),
+            duration,
+        });
     }
 
     fn update(&mut self, dur: u128) -> io::Result<()> {
+        for p in self.popups.iter_mut() {
+            p.duration = p.duration.saturating_sub(dur);
+        }
+        self.popups.retain(|p| p.duration > 0);
+        if self.freeze > 0 {
+            self.freeze = self.freeze.saturating_sub(dur);
+            return Ok(());
+        }
+        if self.rules.arcade() {
+            self.release_ghosts(dur);
+        }
         self.update_player(dur)?;
-        self.check_player_vs_ghosts()?;
-        self.update_ghosts(dur);
-        self.check_player_vs_ghosts()?;
-        self.update_fruit(dur);
+        let trails = self.update_ghosts(dur);
+        self.check_player_vs_ghosts(&trails)?;
+        let trail = self.update_fruit(dur);
+        self.check_player_vs_fruit(&trail)?;
         Ok(())
     }
 
     fn move_player(&mut self, pos: Position) -> io::Result<bool> {
         // move may not be valid - return true if valid
+        self.player.eating = matches!(self.board[pos], Square::Dot | Square::Pill);
         match self.board[pos] {
             Square::Dot => {
-                self.score += 10;
+                self.score += self.rules.points().0;
                 self.dots_left -= 1;
                 self.board[pos] = Square::Empty;
+                self.dot_eaten();
+                self.am.waka()?;
             }
             Square::Pill => {
+                self.dot_eaten();
                 self.am.play(Sound::EatPill)?;
                 self.board[pos] = Square::Empty;
-                self.ghosts_are_edible(self.pill_duration);
-                self.score += 50;
-                self.next_ghost_score = 200;
-            }
-            Square::Fruit if self.fruit_duration > 0 => {
-                self.am.play(Sound::EatPill)?;
-                let bonus = self.bonus().value();
-                self.score += bonus;
-                self.fruit_duration = 0;
-
-                {
-                    let mut w = io::BufWriter::new(stdout());
-                    tui::draw_message(&mut w, self, &format!("{bonus}"), false)?;
-                }
-                thread::sleep(time::Duration::from_millis(150));
+                self.ghosts_are_edible(self.rules.fright(self.level).0);
+                self.score += self.rules.points().1;
+                self.next_ghost_score = self.rules.points().2;
             }
             Square::Empty | Square::Fruit | Square::Tunnel | Square::Start => (),
             Square::Wall | Square::Gate | Square::House => return Ok(false),
@@ -403,26 +926,82 @@ impl Game {
         Ok(true)
     }
 
-    fn update_player(&mut self, telaps: u128) -> io::Result<()> {
-        self.player.timecum += telaps;
-        while self.player.timecum > 100 {
-            self.player.timecum -= 100;
-            self.player.anim_frame = (self.player.anim_frame + 1) % 6;
+    // Move pacman one pixel - false if stopped against a wall.
+    // Turns are accepted a few pixels before or after the centre of a square. Pacman then
+    // cuts the corner diagonally - moving towards the centre line while already moving the
+    // new way.
+    fn step_player(&mut self) -> io::Result<bool> {
+        let p = &mut self.player;
+        // take the next queued turn when possible - skip it if the one after can be taken now
+        while let Some(&want) = p.turns.front() {
+            if want == p.moving {
+                p.turns.pop_front();
+            } else if p.can_turn(&self.board, want) {
+                p.moving = want;
+                p.turns.pop_front();
+                break;
+            } else if p.turns.len() > 1 && p.can_turn(&self.board, p.turns[1]) {
+                p.turns.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let centre = |x: i32| x - x.signum();
+        match p.moving {
+            Left | Right => p.offset.1 = centre(p.offset.1),
+            Up | Down => p.offset.0 = centre(p.offset.0),
+        }
+        if p.ahead(p.moving) >= 0 && !self.board.is_open(p.pos.go(p.moving)) {
+            return Ok(false);
         }
 
+        let half = rules::PIXELS as i32 / 2;
+        let moving = p.moving;
+        let (d, wrap) = match moving {
+            Right => (&mut p.offset.0, 1),
+            Left => (&mut p.offset.0, -1),
+            Down => (&mut p.offset.1, 1),
+            Up => (&mut p.offset.1, -1),
+        };
+        *d += wrap;
+        p.anim_px += 1;
+        if p.anim_px == ANIM_PX {
+            p.anim_px = 0;
+            p.anim_frame = (p.anim_frame + 1) % 6;
+        }
+        if *d >= half || *d < -half {
+            // crossed into the next square
+            *d -= wrap * rules::PIXELS as i32;
+            let next = p.pos.go(moving);
+            self.move_player(next)?;
+        }
+        Ok(true)
+    }
+
+    fn update_player(&mut self, telaps: u128) -> io::Result<()> {
         let prev_score = self.score;
 
-        // Try moving in input direction, then fallback to current movement
-        match self.move_player(self.player.pos.go(self.player.last_input_direction))? {
-            true => self.player.moving = self.player.last_input_direction,
-            false => {
-                if !self.move_player(self.player.pos.go(self.player.moving))? {
-                    return Ok(());
-                }
+        let speeds = self.rules.speeds(self.level);
+        let speed = match (
+            self.ghosts.iter().any(|g| g.edible_duration > 0),
+            self.player.eating,
+        ) {
+            (false, false) => speeds.pacman,
+            (false, true) => speeds.pacman_dots,
+            (true, false) => speeds.pacman_fright,
+            (true, true) => speeds.pacman_fright_dots,
+        };
+        for _ in 0..rules::steps(&mut self.player.progress, speed, telaps, rules::PIXELS) {
+            if !self.step_player()? {
+                self.player.progress = 0; // stopped against a wall
+                self.am.stop_waka();
+                break;
             }
         }
 
-        if prev_score < 10000 && self.score >= 10000 && self.lives < MAX_PACMAN_LIVES {
+        let extra = self.rules.extra_life();
+        if prev_score < extra && self.score >= extra && self.lives < MAX_PACMAN_LIVES {
             self.lives += 1;
             self.am.play(Sound::ExtraLives)?;
         }
@@ -434,26 +1013,29 @@ impl Game {
     } // update_player
 
     // one full game
-    fn run(&mut self) -> io::Result<()> {
-        tui::render_game_info()?;
+    fn run(&mut self, r: &mut dyn Renderer) -> io::Result<()> {
+        tui::render_game_info(r, self)?;
         loop {
-            tui::draw_dynamic(self)?;
-            {
-                let mut w = io::BufWriter::new(stdout());
-                tui::draw_message(&mut w, self, "READY!", false)?;
-            }
+            tui::draw_dynamic(r, self)?;
+            tui::draw_message(r, self, "READY!", false)?;
             thread::sleep(time::Duration::from_millis(1200));
 
-            match game_loop(self)? {
+            match game_loop(self, r)? {
                 GameState::UserQuit => break,
                 GameState::SheetComplete => {
+                    if let Some(act) = self.rules.intermission(self.level)
+                        && !self.intermission(r, act)?
+                    {
+                        break;
+                    }
                     self.level += 1;
                     self.repopulate_board();
-                    tui::clear_screen()?; // next board may have different height
-                    tui::render_game_info()?;
+                    tui::render_game_info(r, self)?; // next board may have different height
                     self.reset_ghosts();
                     self.player = Player::new(self.board.pacman_start);
                     self.timecum = 0;
+                    self.last_period = Period::Scatter;
+                    self.popups.clear();
                 }
                 GameState::LifeLost => {
                     if self.lives == 0 {
@@ -462,105 +1044,183 @@ impl Game {
                     self.lives -= 1;
                     thread::sleep(time::Duration::from_millis(100));
                     self.reset_ghosts();
+                    self.popups.clear();
+                    self.bonus_fruit = None;
+                    self.global_dots = Some(0);
+                    self.elroy_suspended = true;
+                    self.since_dot = 0;
+                    self.seed = 0;
                     self.player = Player::new(self.board.pacman_start);
                 }
             };
         }
-        let mut w = io::BufWriter::new(stdout());
-        tui::draw_message(&mut w, self, "GAME  OVER", true)
+        tui::draw_message(r, self, "GAME  OVER", true)
+    }
+
+    // play an intermission - a key press skips it (not the arrows, which may still be held from
+    // the level). Returns false if the player quits
+    fn intermission(&mut self, r: &mut dyn Renderer, act: Act) -> io::Result<bool> {
+        self.am.play(Sound::Intermission)?;
+        let mut scene = Cutscene::new(act, self.board.width as i32);
+        let mut quit = false;
+        while !scene.done() {
+            let start = time::Instant::now();
+            tui::draw_cutscene(r, self, &scene)?;
+            let mut skip = false;
+            for input in tui::poll_input()? {
+                match input {
+                    tui::InputEvent::Quit => quit = true,
+                    tui::InputEvent::Skip | tui::InputEvent::Cheat => skip = true,
+                    tui::InputEvent::Resize => tui::render_game_info(r, self)?, // Re-draw titles and scores
+                    _ => (),
+                }
+            }
+            if quit || skip {
+                break;
+            }
+            let frame_time = Duration::from_millis(FRAME_MS);
+            if start.elapsed() < frame_time {
+                thread::sleep(frame_time - start.elapsed());
+            }
+            scene.update(FRAME_MS as u128);
+        }
+        self.am.stop(Channel::Music);
+        Ok(!quit)
     }
 } // impl Game
 
-fn game_loop(game: &mut Game) -> io::Result<GameState> {
+fn game_loop(game: &mut Game, r: &mut dyn Renderer) -> io::Result<GameState> {
     let mut flash_frames_left: Option<u32> = None;
     let mut death_frames_left: Option<usize> = None;
+    let mut marquee_timecum = 0;
 
     loop {
         let start = time::Instant::now();
 
-        // adjust overall speed by level
-        let base_speed = if flash_frames_left.is_some() {
+        // movement speed is set by the speed tables - frames are short enough to keep it smooth
+        let frame_ms = if flash_frames_left.is_some() {
             300
         } else if death_frames_left.is_some() {
             150
         } else {
-            match game.level {
-                0 => 140,
-                1..=3 => 130,
-                _ => 120,
-            }
-        };
-        // faster if power pill eaten
-        let speed_boost = if flash_frames_left.is_none()
-            && death_frames_left.is_none()
-            && game.ghosts.iter().any(|g| g.edible_duration > 0)
-        {
-            20
-        } else {
-            0
+            FRAME_MS
         };
-        let target_frame_time = Duration::from_millis(base_speed - speed_boost);
+        let target_frame_time = Duration::from_millis(frame_ms);
         if start.elapsed() < target_frame_time {
             thread::sleep(target_frame_time - start.elapsed());
         }
 
-        match tui::poll_input()? {
-            tui::InputEvent::Quit => return Ok(GameState::UserQuit),
-            tui::InputEvent::Pause => tui::pause(game)?,
-            tui::InputEvent::Resize => {
-                tui::clear_screen()?;
-                tui::render_game_info()?; // Re-draw titles and scores
+        for input in tui::poll_input()? {
+            match input {
+                tui::InputEvent::Quit => {
+                    game.am.stop(Channel::Siren);
+                    game.am.stop(Channel::Effects); // and the ghost cues
+                    return Ok(GameState::UserQuit);
+                }
+                tui::InputEvent::Pause => {
+                    game.am.pause();
+                    tui::pause(r, game)?;
+                    game.am.resume();
+                }
+                tui::InputEvent::Resize => tui::render_game_info(r, game)?, // Re-draw titles and scores
+                tui::InputEvent::Direction(dir) => game.player.queue_turn(dir),
+                tui::InputEvent::Cheat | tui::InputEvent::Skip => {}
             }
-            tui::InputEvent::Direction(dir) => game.player.last_input_direction = dir,
-            _ => {}
         }
 
-        game.mq_idx = (game.mq_idx + 1) % MARQUEE.len(); // scroll marquee
+        // scroll marquee
+        marquee_timecum += frame_ms;
+        while marquee_timecum >= MARQUEE_MS {
+            marquee_timecum -= MARQUEE_MS;
+            game.mq_idx = (game.mq_idx + 1) % MARQUEE.len();
+        }
 
-        let mut w = io::BufWriter::new(stdout());
         if let Some(count) = flash_frames_left {
             // --- VICTORY FLASH ---
             if count == 0 {
                 return Ok(GameState::SheetComplete);
             }
-            tui::draw_board(&mut w, game, count % 2 == 0)?;
-            tui::render_rhs(&mut w, game)?;
-            w.flush()?;
+            tui::draw_board(r, game, count % 2 == 0)?;
+            tui::render_rhs(r, game)?;
+            r.flush()?;
             flash_frames_left = Some(count - 1);
         } else if let Some(count) = death_frames_left {
             // --- DEATH ANIMATION ---
             if count == 0 {
                 return Ok(GameState::LifeLost);
             }
-            tui::draw_board(&mut w, game, false)?;
-            tui::draw_death_frame(&mut w, game, 12 - count)?;
-            tui::render_rhs(&mut w, game)?;
-            w.flush()?;
+            tui::draw_board(r, game, false)?;
+            tui::draw_death_frame(r, game, 12 - count)?;
+            tui::render_rhs(r, game)?;
+            r.flush()?;
             death_frames_left = Some(count - 1);
         } else {
             // --- NORMAL GAMEPLAY ---
-            game.update((time::Instant::now() - start).as_millis())?;
-            tui::draw_dynamic(game)?;
+            game.update(FRAME_MS as u128)?; // game time - steady even if a frame runs late
+            tui::draw_dynamic(r, game)?;
+            game.am.siren(game.siren())?;
+            game.am.ghost_cues(game.ghost_cues())?;
 
             if game.player.dead {
-                game.am.play(Sound::Die).map_err(io::Error::other)?;
+                game.am.play(Sound::Die)?; // cuts the siren
                 death_frames_left = Some(12); // Start 12-frame death animation
             }
 
             if game.dots_left == 0 {
-                game.am.play(Sound::OpeningSong).map_err(io::Error::other)?;
+                game.am.play(Sound::OpeningSong)?;
                 flash_frames_left = Some(10);
             }
 
             // Fruit Logic
-            if matches!(game.dots_left, 74 | 174) {
-                game.fruit_duration = 1000 * (10 + rand::random_range(0..3));
-                game.dots_left -= 1;
+            if game.fruit_due() {
+                game.release_fruit();
             }
         }
     }
 }
 
+struct Options {
+    rules: Ruleset,
+    volumes: Vec<(Channel, u8)>, // percent
+    ghost_cues: bool,
+    synth: bool,
+    layout: tui::Layout,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut opts = Options {
+        rules: Ruleset::Classic,
+        volumes: Vec::new(),
+        ghost_cues: false,
+        synth: false,
+        layout: tui::Layout::Narrow,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => opts.rules = args.next().ok_or("--rules needs a value")?.parse()?,
+            "--volume" => {
+                let v = args.next().ok_or("--volume needs a value")?;
+                let (channel, pct) = v
+                    .split_once('=')
+                    .ok_or(format!("Bad volume: {v} (expected <channel>=<percent>)"))?;
+                let pct = pct
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&p| p <= 100)
+                    .ok_or(format!("Bad volume: {pct} (expected 0-100)"))?;
+                opts.volumes.push((channel.parse()?, pct));
+            }
+            "--ghost-cues" => opts.ghost_cues = true,
+            "--synth" => opts.synth = true,
+            "--wide" => opts.layout = tui::Layout::Wide,
+            "--hires" => opts.layout = tui::Layout::HalfBlock,
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+    Ok(opts)
+}
+
 fn main() -> io::Result<()> {
     // make sure crossterm doesn't leave the terminal in a raw state in case of panics
     let original_hook = std::panic::take_hook();
@@ -570,13 +1230,152 @@ fn main() -> io::Result<()> {
         original_hook(panic_info);
     }));
 
+    let opts = match parse_args() {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!(
+                "{e}\nUsage: rspacman [--rules classic|arcade|mspacman|vcs] \
+                 [--volume music|effects|siren=<percent>]... [--ghost-cues] [--synth] \
+                 [--wide|--hires]"
+            );
+            std::process::exit(2);
+        }
+    };
+
     tui::init_render()?;
+    let mut term = tui::Terminal::new();
     loop {
-        let mut game = Game::new();
-        game.run()?;
-        if !tui::another_game(&mut game)? {
+        let set = match (opts.rules, opts.synth) {
+            (Ruleset::Vcs, _) => SoundSet::Vcs,
+            (_, true) => SoundSet::Synth,
+            (_, false) => SoundSet::Files,
+        };
+        let mut am = AM::new(set);
+        for &(channel, volume) in opts.volumes.iter() {
+            am.set_volume(channel, volume);
+        }
+        if opts.ghost_cues {
+            am.enable_ghost_cues();
+        }
+        let mut game = Game::new(opts.rules, am);
+        game.layout = opts.layout;
+        game.run(&mut term)?;
+        if !tui::another_game(&mut term, &mut game)? {
             break;
         }
     }
     tui::close_render()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Maze;
+    use crate::maze::WIDTH;
+
+    #[test]
+    fn test_turn_queue() {
+        let mut p = Player::new(Position::from_xy(1, 1));
+        for d in [Up, Up, Right, Down] {
+            p.queue_turn(d);
+        }
+        assert_eq!(p.turns, [Right, Down]);
+    }
+
+    // pacman is a pixel from crossing from a into the next square, and the ghost there a moment
+    // from moving into a - one short update and they have passed each other
+    fn cross(game: &mut Game, a: Position, dir: Direction) {
+        let b = a.go(dir);
+        let almost = 100 * rules::TILE_MS - 1;
+        game.player = Player::new(a);
+        game.player.moving = dir;
+        game.player.offset.0 = if dir == Right { 3 } else { -4 };
+        game.player.progress = almost;
+        let g = &mut game.ghosts[1];
+        (g.pos, g.direction, g.state, g.progress) =
+            (b, dir.opposite(), GhostState::Outside, almost);
+        game.update(10).unwrap();
+        assert!(game.player.pos == b && game.ghosts[1].pos == a);
+    }
+
+    #[test]
+    fn test_collision_head_on() {
+        let mut game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        cross(&mut game, Position::from_xy(3, 1), Right);
+        assert!(game.player.dead);
+    }
+
+    #[test]
+    fn test_collision_tunnel() {
+        let mut game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        game.board = Board::new(Maze::Regular);
+        let row = (0..game.board.height)
+            .find(|&r| game.board[Position::from_xy(0, r)] == Square::Tunnel)
+            .unwrap();
+        // across the wrap - pacman goes from the left edge to the right, the ghost the other way
+        cross(&mut game, Position::from_xy(0, row), Left);
+        assert!(game.player.pos == Position::from_xy(WIDTH - 1, row));
+        assert!(game.player.dead);
+    }
+
+    #[test]
+    fn test_collision_edible() {
+        let mut game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        game.ghosts[1].edible_duration = 5000;
+        game.next_ghost_score = 200;
+        let score = game.score;
+        cross(&mut game, Position::from_xy(3, 1), Right);
+        assert!(!game.player.dead);
+        assert!(game.ghosts[1].state == GhostState::Dead);
+        assert!(game.score >= score + 200 && game.freeze > 0);
+    }
+
+    #[test]
+    fn test_clyde_target() {
+        let mut game = Game::new(Ruleset::Arcade, AM::new(SoundSet::Synth));
+        game.player = Player::new(Position::from_xy(1, 1));
+        let corner = game.arcade_targets(Period::Scatter)[3];
+        // exactly 8 squares away he still chases pacman
+        game.ghosts[3].pos = Position::from_xy(9, 1);
+        assert_eq!(game.arcade_targets(Period::Chase)[3], (1, 1));
+        game.ghosts[3].pos = Position::from_xy(8, 1);
+        assert_eq!(game.arcade_targets(Period::Chase)[3], corner);
+    }
+
+    #[test]
+    fn test_fruit_route() {
+        let mut game = Game::new(Ruleset::MsPacman, AM::new(SoundSet::Synth));
+        game.release_fruit();
+        let f = game.bonus_fruit.as_ref().unwrap();
+        let (entry, exit) = (f.pos, *f.route.back().unwrap());
+        assert!(entry.row() != exit.row());
+        let mut last = entry;
+        for _ in 0..2000 {
+            if let Some(&pos) = game.update_fruit(FRAME_MS as u128).last() {
+                last = pos;
+            }
+            if game.bonus_fruit.is_none() {
+                break;
+            }
+        }
+        assert!(game.bonus_fruit.is_none() && last == exit);
+    }
+
+    #[test]
+    fn test_elroy_suspended() {
+        let mut game = Game::new(Ruleset::Arcade, AM::new(SoundSet::Synth));
+        game.dots_left = 1;
+        // Clyde in the house at the start of a level makes no difference
+        assert!(game.ghosts[3].state == GhostState::Home);
+        assert_eq!(game.elroy(), 2);
+        // after a life is lost Blinky waits for Clyde
+        game.elroy_suspended = true;
+        assert_eq!(game.elroy(), 0);
+        for g in &mut game.ghosts[..3] {
+            g.state = GhostState::Outside;
+        }
+        game.release_ghosts(rules::release_timer(game.level));
+        assert!(game.ghosts[3].state == GhostState::Leaving);
+        assert_eq!(game.elroy(), 2);
+    }
+}
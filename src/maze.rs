@@ -207,3 +207,29 @@ pub static MAZE_MS_PACMAN_DARK_BLUE: [&str; 31] = [
     "#..........................#", // 29
     "############################", // 30
 ];
+
+// After the Atari VCS home version - long horizontal bars and tunnels at the top and bottom
+// (here on the sides, as the tunnels wrap horizontally)
+pub static MAZE_VCS_PACMAN: [&str; 21] = [
+    "############################", //  0
+    "#P...........##...........P#", //  1
+    "#.####.#####.##.#####.####.#", //  2
+    "#..........................#", //  3
+    "###.##.##.########.##.##.###", //  4
+    ";;;.##.##....##....##.##.;;;", //  5
+    "###.##.#####.##.#####.##.###", //  6
+    "#......##..........##......#", //  7
+    "#.####.##.###--###.##.####.#", //  8
+    "#.####.##.# HHHH #.##.####.#", //  9
+    "#.........# HHHH #.........#", // 10
+    "#.####.##.# HHHH #.##.####.#", // 11
+    "#.####.##.########.##.####.#", // 12
+    "#......##.....$....##......#", // 13
+    "###.##.#####.##.#####.##.###", // 14
+    ";;;.##.......##.......##.;;;", // 15
+    "###.##.##.########.##.##.###", // 16
+    "#.............p............#", // 17
+    "#.####.#####.##.#####.####.#", // 18
+    "#P...........##...........P#", // 19
+    "############################", // 20
+];
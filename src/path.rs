@@ -0,0 +1,225 @@
+// Shortest path navigation over the maze.
+//
+// A DistanceField holds the number of steps from every open square to the nearest of a set of
+// targets. It is computed once per board with a breadth first search, so following it is a
+// cheap table lookup - step() moves one square closer to the target.
+// The DistanceTable holds the path length between every pair of open squares, and Junctions
+// marks the squares where a ghost has a choice of ways to go.
+
+use crate::board::{Board, Direction, Position};
+use std::collections::VecDeque;
+
+pub const UNREACHABLE: u16 = u16::MAX;
+
+// Next move from p to an open square nearer the target by dist - prefer to keep going in the
+// current direction on ties. None if there is no nearer square, or p has no distance
+fn step(
+    board: &Board,
+    p: Position,
+    current: Direction,
+    dist: impl Fn(Position) -> Option<u16>,
+) -> Option<(Direction, Position)> {
+    let here = dist(p)?;
+    board
+        .neighbours(p)
+        .filter(|&(_, q)| board.is_open(q))
+        .filter_map(|(d, q)| Some((dist(q)?, d, q)))
+        .filter(|&(dq, _, _)| dq < here)
+        .min_by_key(|&(dq, d, _)| (dq, d != current))
+        .map(|(_, d, q)| (d, q))
+}
+
+#[derive(Default)]
+pub struct DistanceField {
+    dist: Vec<u16>,
+}
+
+impl DistanceField {
+    pub fn new(board: &Board, targets: &[Position]) -> Self {
+        let mut dist = vec![UNREACHABLE; board.len()];
+        let mut queue = VecDeque::new();
+        for &t in targets {
+            dist[t.index()] = 0;
+            queue.push_back(t);
+        }
+        // moves are reversible (incl. tunnel wrap), so searching from the targets
+        // gives the distance to the targets
+        while let Some(p) = queue.pop_front() {
+            let d = dist[p.index()] + 1;
+            for (_, q) in board.neighbours(p) {
+                if board.is_open(q) && dist[q.index()] == UNREACHABLE {
+                    dist[q.index()] = d;
+                    queue.push_back(q);
+                }
+            }
+        }
+        DistanceField { dist }
+    }
+
+    pub fn get(&self, p: Position) -> u16 {
+        self.dist[p.index()]
+    }
+
+    // Next move towards the target. None if the target is unreachable from p or p is a target
+    pub fn step(
+        &self,
+        board: &Board,
+        p: Position,
+        current: Direction,
+    ) -> Option<(Direction, Position)> {
+        step(board, p, current, |q| Some(self.get(q)))
+    }
+}
+
+// All pairs shortest path lengths between the open squares of a board
+#[derive(Default)]
+pub struct DistanceTable {
+    id: Vec<u16>, // board index -> row/column in dist, UNREACHABLE if not an open square
+    n: usize,
+    dist: Vec<u16>,
+}
+
+impl DistanceTable {
+    pub fn new(board: &Board) -> Self {
+        let mut id = vec![UNREACHABLE; board.len()];
+        let open: Vec<Position> = (0..board.len())
+            .map(|i| Position::from_xy(i % board.width, i / board.width))
+            .filter(|&p| board.is_open(p))
+            .collect();
+        for (i, p) in open.iter().enumerate() {
+            id[p.index()] = i as u16;
+        }
+
+        let n = open.len();
+        let mut dist = vec![UNREACHABLE; n * n];
+        for (i, &p) in open.iter().enumerate() {
+            let field = DistanceField::new(board, &[p]);
+            for (j, &q) in open.iter().enumerate() {
+                dist[i * n + j] = field.get(q);
+            }
+        }
+        DistanceTable { id, n, dist }
+    }
+
+    // path length from a to b - None if either is not an open square or b can not be reached
+    pub fn get(&self, a: Position, b: Position) -> Option<u16> {
+        let (i, j) = (self.id[a.index()], self.id[b.index()]);
+        if i == UNREACHABLE || j == UNREACHABLE {
+            return None;
+        }
+        match self.dist[i as usize * self.n + j as usize] {
+            UNREACHABLE => None,
+            d => Some(d),
+        }
+    }
+
+    // Next move from p on a shortest path to target. None if target can not be reached or p is
+    // the target
+    pub fn step(
+        &self,
+        board: &Board,
+        p: Position,
+        current: Direction,
+        target: Position,
+    ) -> Option<(Direction, Position)> {
+        step(board, p, current, |q| self.get(q, target))
+    }
+}
+
+// Junctions - squares with 3 or more exits, or dead ends
+#[derive(Default)]
+pub struct Junctions {
+    junction: Vec<bool>, // by board index
+}
+
+impl Junctions {
+    pub fn new(board: &Board) -> Self {
+        let junction = (0..board.len())
+            .map(|i| Position::from_xy(i % board.width, i / board.width))
+            .map(|p| {
+                board.is_open(p)
+                    && board
+                        .neighbours(p)
+                        .filter(|&(_, q)| board.is_open(q))
+                        .count()
+                        != 2
+            })
+            .collect();
+        Junctions { junction }
+    }
+
+    pub fn is_junction(&self, p: Position) -> bool {
+        self.junction[p.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Direction::*, Maze, Square};
+
+    #[test]
+    fn test_home_reachable() {
+        for maze in Maze::ALL {
+            let board = Board::new(maze);
+            for i in 0..board.len() {
+                let p = Position::from_xy(i % board.width, i / board.width);
+                if matches!(board[p], Square::Dot | Square::Pill) {
+                    assert_ne!(board.home.get(p), UNREACHABLE, "{maze:?} {i}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_reaches_gate() {
+        let board = Board::new(Maze::Regular);
+        let mut p = board.pacman_start;
+        let mut d = Left;
+        let mut steps = 0;
+        while let Some((nd, np)) = board.home.step(&board, p, d) {
+            (d, p) = (nd, np);
+            steps += 1;
+        }
+        assert!(p == board.front_of_gate1 || p == board.front_of_gate2);
+        assert_eq!(steps, board.home.get(board.pacman_start));
+    }
+
+    #[test]
+    fn test_distance_table() {
+        let board = Board::new(Maze::Pink);
+        let (a, b) = (board.pacman_start, board.front_of_gate1);
+        assert_eq!(board.paths.get(a, b), board.paths.get(b, a));
+        assert_eq!(board.paths.get(a, a), Some(0));
+        assert_eq!(board.paths.get(a, board.gate1), None);
+        let home = board
+            .paths
+            .get(a, b)
+            .min(board.paths.get(a, board.front_of_gate2));
+        assert_eq!(home, Some(board.home.get(a)));
+    }
+
+    #[test]
+    fn test_table_step() {
+        let board = Board::new(Maze::Orange);
+        let ends = board.tunnel_ends();
+        let (mut p, mut d) = (ends[0], Left);
+        let mut steps = 0;
+        while let Some((nd, np)) = board.paths.step(&board, p, d, board.fruit) {
+            (d, p) = (nd, np);
+            steps += 1;
+        }
+        assert!(p == board.fruit);
+        assert_eq!(Some(steps), board.paths.get(ends[0], board.fruit));
+    }
+
+    #[test]
+    fn test_junctions() {
+        let board = Board::new(Maze::Small);
+        // the top left corner turns, the squares along the top are corridor
+        assert!(!board.junctions.is_junction(Position::from_xy(1, 1)));
+        assert!(!board.junctions.is_junction(Position::from_xy(2, 1)));
+        assert!(board.junctions.is_junction(Position::from_xy(6, 1)));
+        assert!(!board.junctions.is_junction(Position::from_xy(0, 0)));
+    }
+}
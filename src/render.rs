@@ -0,0 +1,141 @@
+// Where frames are drawn. The game draws styled text at (column, row) cells through a Renderer -
+// the terminal (see tui.rs), or a TextGrid in memory, which makes frames easy to inspect in tests.
+
+use crossterm::style::{Attributes, ContentStyle, StyledContent};
+use std::fmt::Display;
+use std::io;
+
+pub trait Renderer {
+    // (columns, rows)
+    fn size(&self) -> (u16, u16);
+    fn print(&mut self, col: u16, row: u16, s: &str, style: ContentStyle) -> io::Result<()>;
+    fn clear(&mut self) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+pub fn put<D: Display>(
+    r: &mut dyn Renderer,
+    col: u16,
+    row: u16,
+    s: StyledContent<D>,
+) -> io::Result<()> {
+    r.print(col, row, &s.content().to_string(), *s.style())
+}
+
+// emoji take up two cells
+pub fn char_width(c: char) -> usize {
+    match c {
+        '\u{FE0F}' => 0,
+        '\u{1F000}'.. => 2,
+        _ => 1,
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: Option<char>, // None - covered by the wide character to the left
+    pub style: ContentStyle,
+}
+
+const BLANK: Cell = Cell {
+    ch: Some(' '),
+    style: ContentStyle {
+        foreground_color: None,
+        background_color: None,
+        underline_color: None,
+        attributes: Attributes::none(),
+    },
+};
+
+// A frame in memory - what a renderer draws into before it goes anywhere
+#[derive(Clone, PartialEq)]
+pub struct CellBuffer {
+    pub cols: u16,
+    pub rows: u16,
+    pub cells: Vec<Cell>, // row by row
+}
+
+impl CellBuffer {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        CellBuffer {
+            cols,
+            rows,
+            cells: vec![BLANK; cols as usize * rows as usize],
+        }
+    }
+
+    pub fn row(&self, row: u16) -> &[Cell] {
+        let start = row as usize * self.cols as usize;
+        &self.cells[start..start + self.cols as usize]
+    }
+
+    // text running off the right edge is cut off
+    pub fn print(&mut self, col: u16, row: u16, s: &str, style: ContentStyle) {
+        if row >= self.rows {
+            return;
+        }
+        let start = row as usize * self.cols as usize;
+        let mut col = col as usize;
+        for c in s.chars() {
+            let width = char_width(c);
+            if width == 0 {
+                continue;
+            }
+            if col + width > self.cols as usize {
+                break;
+            }
+            // a wide character half overwritten leaves a blank
+            if self.cells[start + col].ch.is_none() && col > 0 {
+                self.cells[start + col - 1].ch = Some(' ');
+            }
+            if col + width < self.cols as usize && self.cells[start + col + width].ch.is_none() {
+                self.cells[start + col + width].ch = Some(' ');
+            }
+            self.cells[start + col] = Cell { ch: Some(c), style };
+            for i in 1..width {
+                self.cells[start + col + i] = Cell { ch: None, style };
+            }
+            col += width;
+        }
+    }
+}
+
+// The frame as plain text, one String per row
+#[cfg(test)]
+pub struct TextGrid {
+    buf: CellBuffer,
+}
+
+#[cfg(test)]
+impl TextGrid {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        TextGrid {
+            buf: CellBuffer::new(cols, rows),
+        }
+    }
+
+    pub fn row(&self, row: u16) -> String {
+        self.buf.row(row).iter().filter_map(|c| c.ch).collect()
+    }
+}
+
+#[cfg(test)]
+impl Renderer for TextGrid {
+    fn size(&self) -> (u16, u16) {
+        (self.buf.cols, self.buf.rows)
+    }
+
+    fn print(&mut self, col: u16, row: u16, s: &str, style: ContentStyle) -> io::Result<()> {
+        self.buf.print(col, row, s, style);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.buf = CellBuffer::new(self.buf.cols, self.buf.rows);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
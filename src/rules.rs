@@ -0,0 +1,258 @@
+// Rule sets - the original UniPac rules, rules that follow the arcade game closely, or a
+// tribute to the Atari VCS home version.
+// See The Pac-Man Dossier (https://pacman.holenet.info) for the arcade details.
+
+use crate::board::Maze;
+use crate::cutscene::Act;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Ruleset {
+    Classic,
+    Arcade,
+    MsPacman,
+    Vcs,
+}
+
+impl FromStr for Ruleset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Ruleset::Classic),
+            "arcade" => Ok(Ruleset::Arcade),
+            "mspacman" => Ok(Ruleset::MsPacman),
+            "vcs" => Ok(Ruleset::Vcs),
+            _ => Err(format!(
+                "Unknown ruleset: {s} (expected classic, arcade, mspacman or vcs)"
+            )),
+        }
+    }
+}
+
+// Speeds in percent - at 100% a square takes TILE_MS to cross
+pub const TILE_MS: u32 = 105;
+
+pub struct Speeds {
+    pub pacman: u32,
+    pub pacman_dots: u32, // while eating dots
+    pub pacman_fright: u32,
+    pub pacman_fright_dots: u32,
+    pub ghost: u32,
+    pub ghost_tunnel: u32, // also inside the ghost house
+    pub ghost_fright: u32,
+    pub eyes: u32,
+    pub elroy: [u32; 2],
+}
+
+// length of one flash (ms) of the frightened ghosts
+pub const FLASH_MS: u128 = 400;
+
+impl Ruleset {
+    // Ms. Pac-Man is played with the arcade ghost and speed rules too
+    pub fn arcade(&self) -> bool {
+        matches!(self, Ruleset::Arcade | Ruleset::MsPacman)
+    }
+
+    pub fn maze(&self, level: u32) -> Maze {
+        match self {
+            Ruleset::Vcs => Maze::Vcs,
+            Ruleset::MsPacman => match level {
+                0 | 1 => Maze::Pink,
+                2..=4 => Maze::LightBlue,
+                5..=8 => Maze::Orange,
+                9..=12 => Maze::DarkBlue,
+                // then alternating every 4 levels
+                _ if ((level - 13) / 4).is_multiple_of(2) => Maze::Orange,
+                _ => Maze::DarkBlue,
+            },
+            _ => match level {
+                0 => Maze::Small,
+                2 => Maze::Pink,
+                3 => Maze::LightBlue,
+                4 => Maze::Orange,
+                5 => Maze::DarkBlue,
+                _ => Maze::Regular,
+            },
+        }
+    }
+
+    // intermission played after completing a level (0 based)
+    pub fn intermission(&self, level: u32) -> Option<Act> {
+        match (self, level) {
+            (Ruleset::Classic | Ruleset::Vcs, _) => None,
+            (Ruleset::Arcade, 1) => Some(Act::Chase),
+            (Ruleset::Arcade, 4) => Some(Act::Sheet),
+            (Ruleset::Arcade, 8 | 12 | 16) => Some(Act::Patched),
+            (Ruleset::MsPacman, 1) => Some(Act::TheyMeet),
+            (Ruleset::MsPacman, 4 | 12 | 16) => Some(Act::TheChase),
+            (Ruleset::MsPacman, 8) => Some(Act::Junior),
+            _ => None,
+        }
+    }
+
+    // points for a dot, a power pill and the first ghost - the VCS game counted wafers
+    pub fn points(&self) -> (u32, u32, u32) {
+        match self {
+            Ruleset::Vcs => (1, 5, 20),
+            _ => (10, 50, 200),
+        }
+    }
+
+    // score for the extra life - scaled down with the VCS points
+    pub fn extra_life(&self) -> u32 {
+        match self {
+            Ruleset::Vcs => 1000,
+            _ => 10000,
+        }
+    }
+
+    pub fn speeds(&self, level: u32) -> Speeds {
+        match self {
+            // the original UniPac speeds - the whole game gets faster with the level
+            Ruleset::Classic | Ruleset::Vcs => {
+                let (pacman, fright) = match level {
+                    0 => (75, 88),
+                    1..=3 => (81, 95),
+                    _ => (88, 105),
+                };
+                let (ghost, tunnel, ghost_fright) = match level {
+                    0 => (75, 40, 40),
+                    1..=3 => (85, 45, 50),
+                    _ => (95, 50, 55),
+                };
+                Speeds {
+                    pacman,
+                    pacman_dots: pacman,
+                    pacman_fright: fright,
+                    pacman_fright_dots: fright,
+                    ghost: ghost * pacman / 100,
+                    ghost_tunnel: tunnel * pacman / 100,
+                    ghost_fright: ghost_fright * fright / 100,
+                    eyes: pacman,
+                    elroy: [ghost * pacman / 100; 2],
+                }
+            }
+            Ruleset::Arcade | Ruleset::MsPacman => match level {
+                0 => Speeds {
+                    pacman: 80,
+                    pacman_dots: 71,
+                    pacman_fright: 90,
+                    pacman_fright_dots: 79,
+                    ghost: 75,
+                    ghost_tunnel: 40,
+                    ghost_fright: 50,
+                    eyes: 160,
+                    elroy: [80, 85],
+                },
+                1..=3 => Speeds {
+                    pacman: 90,
+                    pacman_dots: 79,
+                    pacman_fright: 95,
+                    pacman_fright_dots: 83,
+                    ghost: 85,
+                    ghost_tunnel: 45,
+                    ghost_fright: 55,
+                    eyes: 160,
+                    elroy: [90, 95],
+                },
+                4..=19 => Speeds {
+                    pacman: 100,
+                    pacman_dots: 87,
+                    pacman_fright: 100,
+                    pacman_fright_dots: 87,
+                    ghost: 95,
+                    ghost_tunnel: 50,
+                    ghost_fright: 60,
+                    eyes: 160,
+                    elroy: [100, 105],
+                },
+                _ => Speeds {
+                    pacman: 90,
+                    pacman_dots: 79,
+                    pacman_fright: 90,
+                    pacman_fright_dots: 79,
+                    ghost: 95,
+                    ghost_tunnel: 50,
+                    ghost_fright: 95,
+                    eyes: 160,
+                    elroy: [100, 105],
+                },
+            },
+        }
+    }
+
+    // Frightened time (ms) after a pill, and the number of flashes before the ghosts recover.
+    // On later arcade levels the ghosts only turn around
+    pub fn fright(&self, level: u32) -> (u128, u32) {
+        match self {
+            Ruleset::Classic | Ruleset::Vcs => (6000, 5),
+            Ruleset::Arcade | Ruleset::MsPacman => match level {
+                0 => (6000, 5),
+                1 | 5 | 9 => (5000, 5),
+                2 => (4000, 5),
+                3 | 13 => (3000, 5),
+                4 | 6 | 7 | 10 => (2000, 5),
+                8 | 11 | 12 | 14 | 15 | 17 => (1000, 3),
+                _ => (0, 0),
+            },
+        }
+    }
+}
+
+// pacman moves in pixels - a square is PIXELS wide
+pub const PIXELS: u32 = 8;
+
+// Ms. Pac-Man's bonus fruit wanders along at this speed
+pub const FRUIT_SPEED: u32 = 40;
+
+// Moving at speed (%) for telaps ms - returns the number of steps (squares / parts) to move now
+// and keeps the remaining fraction of a step in progress
+pub fn steps(progress: &mut u32, speed: u32, telaps: u128, parts: u32) -> u32 {
+    *progress += speed * telaps as u32 * parts;
+    let n = *progress / (100 * TILE_MS);
+    *progress %= 100 * TILE_MS;
+    n
+}
+
+// Ghost house - ghosts are released in this order (Pinky, Inky, Clyde); Blinky starts outside
+pub const RELEASE_ORDER: [usize; 3] = [0, 2, 3];
+
+// dots pacman must eat before a ghost may leave the house (per ghost counters)
+pub fn dot_limit(level: u32, ghost: usize) -> u32 {
+    match (ghost, level) {
+        (2, 0) => 30,
+        (3, 0) => 60,
+        (3, 1) => 50,
+        _ => 0,
+    }
+}
+
+// after a life is lost a global counter is used instead (None - Clyde's limit disables it)
+pub fn global_dot_limit(ghost: usize) -> Option<u32> {
+    match ghost {
+        0 => Some(7),
+        2 => Some(17),
+        _ => None,
+    }
+}
+pub const GLOBAL_DOT_OFF: u32 = 32;
+
+// release the next ghost if pacman hasn't eaten a dot for this long (ms)
+pub fn release_timer(level: u32) -> u128 {
+    if level < 4 { 4000 } else { 3000 }
+}
+
+// Cruise Elroy - Blinky speeds up when fewer dots than this are left (half for the 2nd stage)
+pub fn elroy_dots(level: u32) -> u32 {
+    match level {
+        0 => 20,
+        1 => 30,
+        2..=4 => 40,
+        5..=7 => 50,
+        8..=10 => 60,
+        11..=13 => 80,
+        14..=17 => 100,
+        _ => 120,
+    }
+}
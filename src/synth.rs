@@ -0,0 +1,235 @@
+// Synthesized sounds in the style of the arcade's Namco WSG sound chip - a few voices, each
+// playing a 32 sample, 4 bit waveform at a given frequency and volume.
+//
+// An effect is a list of tones per voice. A tone sweeps from one frequency to another, so
+// the same definition covers notes (no sweep), slides and the siren's wail. Changing an effect
+// is a matter of changing its tones below.
+
+use crate::audio::Sound;
+use kira::{Frame, sound::static_sound::StaticSoundData};
+use std::sync::Arc;
+
+const SAMPLE_RATE: u32 = 44100;
+
+type Wave = [u8; 32];
+
+const SINE: Wave = [
+    8, 9, 10, 12, 13, 14, 14, 15, 15, 15, 14, 14, 13, 12, 10, 9, 8, 6, 5, 3, 2, 1, 1, 0, 0, 0, 1,
+    1, 2, 3, 5, 6,
+];
+const TRIANGLE: Wave = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4,
+    3, 2, 1, 0,
+];
+const SAW: Wave = [
+    0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13, 14,
+    14, 15, 15,
+];
+const SQUARE: Wave = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0,
+];
+
+#[derive(Copy, Clone)]
+struct Tone {
+    wave: &'static Wave,
+    from: f32, // Hz
+    to: f32,
+    ms: u32,
+    volume: u8, // 0..15
+}
+
+const fn sweep(wave: &'static Wave, from: f32, to: f32, ms: u32) -> Tone {
+    Tone {
+        wave,
+        from,
+        to,
+        ms,
+        volume: 12,
+    }
+}
+
+const fn rest(ms: u32) -> Tone {
+    Tone {
+        wave: &SINE,
+        from: 0.0,
+        to: 0.0,
+        ms,
+        volume: 0,
+    }
+}
+
+// midi note number
+fn note(wave: &'static Wave, midi: i32, ms: u32) -> Tone {
+    let hz = 440.0 * 2f32.powf((midi - 69) as f32 / 12.0);
+    sweep(wave, hz, hz, ms)
+}
+
+// a melody - (midi note, length in units of ms)
+fn melody(wave: &'static Wave, notes: &[(i32, u32)], ms: u32) -> Vec<Tone> {
+    notes
+        .iter()
+        .map(|&(midi, len)| match midi {
+            0 => rest(len * ms),
+            _ => note(wave, midi, len * ms),
+        })
+        .collect()
+}
+
+fn voices(name: Sound) -> Vec<Vec<Tone>> {
+    match name {
+        Sound::Eating => vec![vec![sweep(&TRIANGLE, 220.0, 660.0, 90)]],
+        Sound::EatingShort => vec![vec![sweep(&TRIANGLE, 660.0, 220.0, 90)]],
+        // one rise and fall - it loops, and the pitch is raised as the board is cleared
+        Sound::Siren => vec![vec![
+            sweep(&SINE, 380.0, 760.0, 200),
+            sweep(&SINE, 760.0, 380.0, 200),
+        ]],
+        // the loops while the ghosts are frightened - a low warble - and while their eyes
+        // hurry home - a high, fast whoop
+        Sound::Fright => vec![vec![sweep(&SQUARE, 150.0, 450.0, 130)]],
+        Sound::Eyes => vec![vec![sweep(&SINE, 1600.0, 600.0, 70)]],
+        Sound::EatPill => vec![vec![
+            sweep(&SQUARE, 300.0, 1200.0, 80),
+            sweep(&SQUARE, 1200.0, 600.0, 60),
+        ]],
+        Sound::EatGhost => vec![vec![sweep(&SAW, 100.0, 1600.0, 500)]],
+        Sound::ExtraLives => vec![[sweep(&SQUARE, 1050.0, 1050.0, 60), rest(60)].repeat(8)],
+        // falling wails, each shorter than the last, then two blips
+        Sound::Die => {
+            let mut tones: Vec<Tone> = (0..10)
+                .map(|i| sweep(&SAW, 900.0 - 50.0 * i as f32, 200.0, 150 - 8 * i))
+                .collect();
+            tones.extend([
+                rest(100),
+                sweep(&SINE, 200.0, 1000.0, 80),
+                rest(60),
+                sweep(&SINE, 200.0, 1000.0, 80),
+            ]);
+            vec![tones]
+        }
+        Sound::OpeningSong => {
+            #[rustfmt::skip]
+            let tune = [
+                (71, 2), (83, 2), (78, 2), (75, 2), (83, 1), (78, 3), (75, 4),
+                (72, 2), (84, 2), (79, 2), (76, 2), (84, 1), (79, 3), (76, 4),
+                (71, 2), (83, 2), (78, 2), (75, 2), (83, 1), (78, 3), (75, 4),
+                (75, 1), (76, 1), (77, 2), (77, 1), (78, 1), (79, 2), (79, 1), (80, 1),
+                (81, 2), (83, 4),
+            ];
+            #[rustfmt::skip]
+            let bass = [
+                (47, 6), (59, 2), (47, 6), (59, 2),
+                (48, 6), (60, 2), (48, 6), (60, 2),
+                (47, 6), (59, 2), (47, 6), (59, 2),
+                (54, 4), (56, 4), (58, 4), (59, 4),
+            ];
+            vec![melody(&SINE, &tune, 65), melody(&TRIANGLE, &bass, 65)]
+        }
+        Sound::Intermission => {
+            #[rustfmt::skip]
+            let tune = [
+                (77, 2), (0, 2), (77, 2), (74, 2), (77, 4), (0, 4),
+                (77, 2), (0, 2), (77, 2), (74, 2), (77, 4), (0, 4),
+                (79, 2), (77, 2), (76, 2), (74, 2), (72, 2), (74, 2), (76, 2), (77, 2),
+                (79, 4), (72, 4), (77, 8),
+            ];
+            #[rustfmt::skip]
+            let bass = [
+                (41, 4), (53, 4), (41, 4), (53, 4),
+                (41, 4), (53, 4), (41, 4), (53, 4),
+                (46, 4), (58, 4), (48, 4), (60, 4),
+                (48, 4), (48, 4), (41, 8),
+            ];
+            vec![
+                melody(&SQUARE, &tune, 75).repeat(2),
+                melody(&TRIANGLE, &bass, 75).repeat(2),
+            ]
+        }
+    }
+}
+
+// The Atari VCS's TIA chip divides its clock down for the pitch - only a few coarse notes,
+// and square waves (the VCS tune itself comes from its audio file when there is one)
+fn tia(div: u32, ms: u32) -> Tone {
+    let hz = 31400.0 / 6.0 / (div + 1) as f32;
+    sweep(&SQUARE, hz, hz, ms)
+}
+
+fn vcs_voices(name: Sound) -> Vec<Vec<Tone>> {
+    match name {
+        Sound::Eating => vec![vec![tia(9, 40), tia(6, 40)]],
+        Sound::EatingShort => vec![vec![tia(6, 40), tia(9, 40)]],
+        Sound::Siren => vec![vec![tia(24, 100), tia(26, 100), tia(28, 100), tia(26, 100)]],
+        Sound::Fright => vec![vec![tia(31, 60), tia(29, 60)]],
+        Sound::Eyes => vec![(2..8).map(|i| tia(i, 15)).collect()],
+        Sound::EatPill => vec![(0..6).rev().map(|i| tia(2 * i, 30)).collect()],
+        Sound::EatGhost => vec![(0..20).rev().map(|i| tia(i, 25)).collect()],
+        Sound::ExtraLives => vec![[tia(5, 80), rest(80)].repeat(4)],
+        Sound::Die => vec![(0..32).map(|i| tia(i, 40)).collect()],
+        Sound::OpeningSong | Sound::Intermission => vec![
+            [12, 10, 8, 10, 12, 15, 12, 8]
+                .iter()
+                .map(|&d| tia(d, 150))
+                .collect(),
+        ],
+    }
+}
+
+pub fn sound(name: Sound) -> StaticSoundData {
+    render(voices(name))
+}
+
+pub fn vcs_sound(name: Sound) -> StaticSoundData {
+    render(vcs_voices(name))
+}
+
+// Mix the voices of a sound into samples
+fn render(voices: Vec<Vec<Tone>>) -> StaticSoundData {
+    let len = voices
+        .iter()
+        .map(|v| v.iter().map(|t| t.ms).sum::<u32>())
+        .max()
+        .unwrap_or(0) as usize
+        * SAMPLE_RATE as usize
+        / 1000;
+    let mut samples = vec![0.0f32; len];
+    for tones in voices.iter() {
+        let (mut i, mut phase) = (0, 0.0f32);
+        for t in tones {
+            let n = (t.ms * SAMPLE_RATE / 1000) as usize;
+            for k in 0..n.min(len - i) {
+                let hz = t.from + (t.to - t.from) * k as f32 / n as f32;
+                phase = (phase + hz / SAMPLE_RATE as f32).fract();
+                let level = t.wave[(phase * 32.0) as usize] as f32 / 7.5 - 1.0;
+                samples[i + k] += level * t.volume as f32 / 15.0 / voices.len() as f32;
+            }
+            i += n.min(len - i);
+        }
+    }
+    // the chip is loud - leave some headroom for the other channels
+    let frames: Vec<Frame> = samples
+        .into_iter()
+        .map(|s| Frame::from_mono(0.5 * s))
+        .collect();
+    StaticSoundData {
+        sample_rate: SAMPLE_RATE,
+        frames: Arc::from(frames),
+        settings: Default::default(),
+        slice: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sound_lengths() {
+        let siren = sound(Sound::Siren);
+        assert_eq!(siren.frames.len(), 400 * SAMPLE_RATE as usize / 1000);
+        let intro = sound(Sound::OpeningSong);
+        assert_eq!(intro.frames.len(), 64 * 65 * SAMPLE_RATE as usize / 1000);
+        assert!(intro.frames.iter().all(|f| f.left.abs() <= 0.5));
+    }
+}
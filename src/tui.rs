@@ -1,14 +1,18 @@
 use crate::{
-    Fruit, Game, GhostState, MARQUEE, MAX_PACMAN_LIVES, Period, Position,
-    board::{Direction, Square},
+    FRAME_MS, Fruit, Game, GhostState, MARQUEE, MAX_PACMAN_LIVES, Period, Position,
+    board::{Direction, Maze, Square},
+    cutscene::{Actor, Cutscene, Sprite},
+    halfblock::{self, Canvas},
+    render::{CellBuffer, Renderer, char_width, put},
+    rules::{FLASH_MS, Ruleset},
 };
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode, poll, read},
-    style::{self, Stylize},
+    style::{self, ContentStyle, Stylize},
     terminal,
 };
-use std::io::{self, Write, stdout};
+use std::io::{self, BufWriter, Stdout, Write, stdout};
 use std::time::Duration;
 
 fn get_fruit_symbol(fruit: Fruit) -> &'static str {
@@ -21,9 +25,157 @@ fn get_fruit_symbol(fruit: Fruit) -> &'static str {
         Fruit::Galaxian => "\u{1F680}", // rocket ship
         Fruit::Bell => "\u{1F514}",
         Fruit::Key => "\u{1F511}",
+        Fruit::Orange => "\u{1F34A}",
+        Fruit::Pretzel => "\u{1F968}",
+        Fruit::Pear => "\u{1F350}",
+        Fruit::Banana => "\u{1F34C}",
+        Fruit::Vitamin => "\u{1F48A}", // pill
     }
 }
 
+// How the board is laid out on the screen. In the wide layout every square is two columns,
+// the width of the emoji sprites, so the ghosts and the fruit cover just their own square.
+// The half block layout draws the board in pixels (see halfblock.rs), 4x4 to a square
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Layout {
+    Narrow,
+    Wide,
+    HalfBlock,
+}
+
+impl Layout {
+    // (columns, rows) per square
+    fn scale(self) -> (usize, usize) {
+        match self {
+            Layout::Narrow => (1, 1),
+            Layout::Wide => (2, 1),
+            Layout::HalfBlock => (4, 2),
+        }
+    }
+}
+
+const SQUARE_PX: i32 = 4;
+
+// the screen column of a board column
+fn screen_col(game: &Game, col: usize) -> u16 {
+    (col * game.layout.scale().0) as u16
+}
+
+fn screen_row(game: &Game, row: usize) -> u16 {
+    (row * game.layout.scale().1) as u16
+}
+
+// width of the board on the screen
+fn board_cols(game: &Game) -> usize {
+    game.board.width * game.layout.scale().0
+}
+
+// Ms. Pac-Man has a colour scheme for each maze - walls and dots
+fn maze_colors(game: &Game) -> (style::Color, style::Color) {
+    let rgb = |r, g, b| style::Color::Rgb { r, g, b };
+    match (game.rules, game.board.maze) {
+        (Ruleset::MsPacman, Maze::Pink) => (rgb(255, 183, 174), rgb(222, 222, 255)),
+        (Ruleset::MsPacman, Maze::LightBlue) => (rgb(71, 183, 255), rgb(255, 255, 0)),
+        (Ruleset::MsPacman, Maze::Orange) => (rgb(222, 151, 81), rgb(255, 0, 0)),
+        (Ruleset::MsPacman, Maze::DarkBlue) => (rgb(33, 33, 255), rgb(255, 255, 255)),
+        (Ruleset::Vcs, _) => (rgb(210, 164, 74), rgb(210, 164, 74)),
+        _ => (style::Color::Blue, style::Color::White),
+    }
+}
+
+// The crossterm terminal. The game draws into a buffer, and each flush sends only the cells
+// that changed since the last one - a frame is mostly the same as the one before, which matters
+// over slow links
+pub struct Terminal {
+    w: BufWriter<Stdout>,
+    back: CellBuffer,
+    front: Option<CellBuffer>, // what is on the screen - None when it needs to be cleared
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        Terminal {
+            w: BufWriter::new(stdout()),
+            back: CellBuffer::new(cols, rows),
+            front: None,
+        }
+    }
+}
+
+impl Renderer for Terminal {
+    fn size(&self) -> (u16, u16) {
+        (self.back.cols, self.back.rows)
+    }
+
+    fn print(&mut self, col: u16, row: u16, s: &str, style: ContentStyle) -> io::Result<()> {
+        self.back.print(col, row, s, style);
+        Ok(())
+    }
+
+    // also picks up a new terminal size
+    fn clear(&mut self) -> io::Result<()> {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        self.back = CellBuffer::new(cols, rows);
+        self.front = None;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let front = match self.front.take() {
+            Some(front) => front,
+            None => {
+                crossterm::queue!(self.w, terminal::Clear(terminal::ClearType::All))?;
+                CellBuffer::new(self.back.cols, self.back.rows)
+            }
+        };
+        write_changes(&mut self.w, &front, &self.back)?;
+        self.front = Some(self.back.clone());
+        self.w.flush()
+    }
+}
+
+// The commands to turn the screen from front into back. The cursor is only moved when the next
+// change isn't where the last one left it, and the style only set when it changes.
+fn write_changes<W: Write>(w: &mut W, front: &CellBuffer, back: &CellBuffer) -> io::Result<()> {
+    let mut cursor = None;
+    let mut style = None;
+    for row in 0..back.rows {
+        for (col, (old, new)) in front.row(row).iter().zip(back.row(row)).enumerate() {
+            let Some(c) = new.ch else {
+                continue;
+            };
+            if old == new {
+                continue;
+            }
+            let col = col as u16;
+            if cursor != Some((col, row)) {
+                crossterm::queue!(w, cursor::MoveTo(col, row))?;
+            }
+            if style != Some(new.style) {
+                crossterm::queue!(
+                    w,
+                    style::SetAttribute(style::Attribute::Reset),
+                    style::SetStyle(new.style)
+                )?;
+                style = Some(new.style);
+            }
+            crossterm::queue!(w, style::Print(c))?;
+            // terminals don't all agree on how wide emoji are
+            cursor = match char_width(c) {
+                1 => Some((col + 1, row)),
+                _ => None,
+            };
+        }
+    }
+    crossterm::queue!(w, style::SetAttribute(style::Attribute::Reset))
+}
+
+// plain text in the terminal's colours
+fn print(r: &mut dyn Renderer, col: u16, row: u16, s: &str) -> io::Result<()> {
+    r.print(col, row, s, ContentStyle::new())
+}
+
 pub fn init_render() -> io::Result<()> {
     terminal::enable_raw_mode()?;
     crossterm::execute!(
@@ -32,10 +184,23 @@ pub fn init_render() -> io::Result<()> {
         terminal::Clear(terminal::ClearType::All),
         cursor::Hide,
         cursor::MoveTo(0, 0)
-    )
+    )?;
+    // ask for key repeat/release events, where the terminal supports it
+    if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+        crossterm::execute!(
+            stdout(),
+            event::PushKeyboardEnhancementFlags(
+                event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )
+        )?;
+    }
+    Ok(())
 }
 
 pub fn close_render() -> io::Result<()> {
+    if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+        crossterm::queue!(stdout(), event::PopKeyboardEnhancementFlags)?;
+    }
     crossterm::queue!(
         stdout(),
         terminal::Clear(terminal::ClearType::All),
@@ -46,49 +211,54 @@ pub fn close_render() -> io::Result<()> {
     terminal::disable_raw_mode()
 }
 
-pub fn centered_x(s: &str) -> u16 {
-    let leftedge: u16 = 32;
-    let cols = terminal::size().map(|(c, _)| c).unwrap_or(80);
+// centered right of the board
+pub fn centered_x(r: &dyn Renderer, game: &Game, s: &str) -> u16 {
+    let leftedge = board_cols(game) as u16 + 4;
+    let cols = r.size().0;
     let n: u16 = s.len() as u16;
     let offset = cols.saturating_sub(leftedge).saturating_sub(n) / 2;
     offset + leftedge
 }
 
-pub fn draw_message<W: Write>(w: &mut W, game: &Game, s: &str, blink: bool) -> io::Result<()> {
-    let col = ((game.board.width - s.len()) / 2) as u16;
+pub fn draw_message(r: &mut dyn Renderer, game: &Game, s: &str, blink: bool) -> io::Result<()> {
+    let col = ((board_cols(game) - s.len()) / 2) as u16;
     let styled = if blink {
         s.bold().slow_blink()
     } else {
         s.bold()
     };
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(col, game.board.fruit.row() as u16),
-        style::PrintStyledContent(styled)
-    )?;
-    w.flush()
+    put(r, col, screen_row(game, game.board.fruit.row()), styled)?;
+    r.flush()
 }
 
-pub fn draw_message_at<W: Write>(w: &mut W, game: &Game, pos: Position, s: &str) -> io::Result<()> {
-    let (col, row) = (
-        std::cmp::min(pos.col(), game.board.width - 4) as u16,
-        pos.row() as u16,
-    );
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(col, row),
-        style::PrintStyledContent(s.bold())
-    )?;
-    w.flush()
+pub fn draw_message_at(
+    r: &mut dyn Renderer,
+    game: &Game,
+    pos: Position,
+    s: &str,
+) -> io::Result<()> {
+    let col = std::cmp::min(screen_col(game, pos.col()), (board_cols(game) - 4) as u16);
+    put(r, col, screen_row(game, pos.row()), s.bold())
 }
 
-pub fn draw_dynamic(game: &Game) -> io::Result<()> {
-    let mut w = io::BufWriter::new(stdout());
-    draw_board(&mut w, game, false)?;
-    draw_player(&mut w, game)?;
-    draw_ghosts(&mut w, game)?;
-    render_rhs(&mut w, game)?;
-    w.flush()
+pub fn draw_dynamic(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
+    if game.layout == Layout::HalfBlock {
+        let mut canvas = board_canvas(game, false);
+        draw_player_px(&mut canvas, game, player_mouth(game.player.anim_frame));
+        draw_ghosts_px(&mut canvas, game);
+        canvas.draw(r, 0, 0)?;
+        draw_fruit(r, game)?;
+    } else {
+        draw_board(r, game, false)?;
+        draw_fruit(r, game)?;
+        draw_player(r, game)?;
+        draw_ghosts(r, game)?;
+    }
+    for p in game.popups.iter() {
+        draw_message_at(r, game, p.pos, &p.text)?;
+    }
+    render_rhs(r, game)?;
+    r.flush()
 }
 
 fn get_wall_char(board: &crate::board::Board, pos: Position) -> &'static str {
@@ -136,92 +306,374 @@ fn get_wall_char(board: &crate::board::Board, pos: Position) -> &'static str {
     }
 }
 
-pub fn draw_board<W: Write>(w: &mut W, game: &Game, bold: bool) -> io::Result<()> {
+pub fn draw_board(r: &mut dyn Renderer, game: &Game, bold: bool) -> io::Result<()> {
+    if game.layout == Layout::HalfBlock {
+        return board_canvas(game, bold).draw(r, 0, 0);
+    }
+    let (wall, dot) = maze_colors(game);
+    let vcs = game.rules == Ruleset::Vcs;
+    let wide = game.layout == Layout::Wide;
     for col in 0..game.board.width {
         for row in 0..game.board.height {
             let p = Position::from_xy(col, row);
             let s = match game.board[p] {
+                // the VCS maze is made of blocks, with wafers instead of dots
+                Square::Wall if vcs => "█".with(wall),
+                Square::Dot if vcs => "-".with(dot),
+                Square::Pill if vcs => "■".slow_blink().with(dot),
                 //Square::Wall => "#".blue(),
-                Square::Wall => get_wall_char(&game.board, p).blue(),
+                Square::Wall => get_wall_char(&game.board, p).with(wall),
                 //Square::Wall => "\u{2588}".blue(), // Full Block
-                Square::Dot => ".".white(),
+                Square::Dot => ".".with(dot),
                 //Square::Pill => "*".slow_blink(),
                 Square::Pill => "●".slow_blink().white(), // Using a rounder dot for pills
                 Square::Gate => "─".white(),
-                Square::Fruit if game.fruit_duration > 0 => continue,
                 _ => " ".white(),
             };
             let s = if bold { s.bold() } else { s };
-            crossterm::queue!(
-                w,
-                cursor::MoveTo(col as u16, row as u16),
-                style::PrintStyledContent(s),
-            )?;
+            let x = screen_col(game, col);
+            put(r, x, row as u16, s)?;
+            if wide {
+                let fill = wide_fill(game, p, vcs, wall);
+                let fill = if bold { fill.bold() } else { fill };
+                put(r, x + 1, row as u16, fill)?;
+            }
         }
     }
 
-    // print fruit separately - because not rendered correctly otherwise (is wider than one cell)
-    if game.fruit_duration > 0 {
-        let fruit = get_fruit_symbol(game.bonus());
-        let (col, row) = (game.board.fruit.col(), game.board.fruit.row());
-        crossterm::queue!(
-            w,
-            cursor::MoveTo(col as u16, row as u16),
-            style::Print(fruit),
-        )?;
+    Ok(())
+}
+
+// the right half of a square in the wide layout - walls reach across to the next square
+fn wide_fill(
+    game: &Game,
+    p: Position,
+    vcs: bool,
+    wall: style::Color,
+) -> style::StyledContent<&'static str> {
+    let board = &game.board;
+    let joined = |p: Position| {
+        p.col() + 1 < board.width
+            && matches!(board[p], Square::Wall | Square::Gate)
+            && matches!(
+                board[Position::from_xy(p.col() + 1, p.row())],
+                Square::Wall | Square::Gate
+            )
+    };
+    match board[p] {
+        Square::Wall if vcs => "█".with(wall),
+        Square::Gate => "─".white(),
+        Square::Wall if joined(p) => "═".with(wall),
+        _ => " ".white(),
+    }
+}
+
+// the fruit is drawn on top of the board - it is wider than one cell and may be moving
+pub fn draw_fruit(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
+    if let Some(f) = &game.bonus_fruit {
+        // in the middle of its square, where that is wider than the fruit
+        let col =
+            screen_col(game, f.pos.col()) + game.layout.scale().0.saturating_sub(2) as u16 / 2;
+        let row = screen_row(game, f.pos.row());
+        print(r, col, row, get_fruit_symbol(game.fruit))?;
     }
     Ok(())
 }
 
-pub fn draw_player<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
-    let ch = match game.player.last_input_direction {
+fn player_char(moving: Direction, anim_frame: usize) -> char {
+    let frames = match moving {
         Direction::Left => ['}', ')', '>', '-', '>', ')'],
         Direction::Right => ['{', '(', '<', '-', '<', '('],
         Direction::Up => ['V', 'V', 'V', 'V', '|', '|'],
         Direction::Down => ['^', '^', '^', '^', '|', '|'],
-    }[game.player.anim_frame];
-    let (col, row) = (game.player.pos.col() as u16, game.player.pos.row() as u16);
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(col, row),
-        style::PrintStyledContent(ch.bold().yellow())
+    };
+    frames[anim_frame]
+}
+
+pub fn draw_player(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
+    let ch = player_char(game.player.moving, game.player.anim_frame);
+    let (col, row) = (
+        screen_col(game, game.player.pos.col()),
+        game.player.pos.row() as u16,
+    );
+    put(r, col, row, ch.bold().yellow())
+}
+
+// The half block layout. The board is drawn in pixels, with walls as rounded outlines
+fn board_canvas(game: &Game, bold: bool) -> Canvas {
+    let board = &game.board;
+    let (wall, dot) = maze_colors(game);
+    // the maze flashes white when the level is cleared
+    let wall = if bold { style::Color::White } else { wall };
+    let vcs = game.rules == Ruleset::Vcs;
+    let is_wall = |col: i32, row: i32| {
+        (0..board.width as i32).contains(&col)
+            && (0..board.height as i32).contains(&row)
+            && board[Position::from_xy(col as usize, row as usize)] == Square::Wall
+    };
+    // a wall square is filled but for a pixel's margin on the sides facing the corridors, so
+    // the outline runs a little inside it - and the corners are rounded
+    let solid = |x: i32, y: i32| {
+        let (col, row) = (x.div_euclid(SQUARE_PX), y.div_euclid(SQUARE_PX));
+        if !is_wall(col, row) {
+            return false;
+        }
+        if vcs {
+            return true;
+        }
+        let (lx, ly) = (x - col * SQUARE_PX, y - row * SQUARE_PX);
+        // the side (-1, 0 or 1) of the square a pixel is on - at the edge, and one further in
+        let side = |l, edge| match l {
+            0 if edge => -1,
+            1 if !edge => -1,
+            2 if !edge => 1,
+            3 if edge => 1,
+            _ => 0,
+        };
+        let (dx, dy) = (side(lx, true), side(ly, true));
+        let margin =
+            !is_wall(col + dx, row) || !is_wall(col, row + dy) || !is_wall(col + dx, row + dy);
+        let (cx, cy) = (side(lx, false), side(ly, false));
+        let corner = cx != 0 && cy != 0 && !is_wall(col + cx, row) && !is_wall(col, row + cy);
+        !margin && !corner
+    };
+    let (w, h) = (
+        board.width as i32 * SQUARE_PX,
+        board.height as i32 * SQUARE_PX,
+    );
+    let mut canvas = Canvas::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let edge = (-1..=1).any(|dy| (-1..=1).any(|dx| !solid(x + dx, y + dy)));
+            if solid(x, y) && (vcs || edge) {
+                canvas.set(x, y, wall);
+            }
+        }
+    }
+    let blink = (game.timecum / 250).is_multiple_of(2);
+    for row in 0..board.height {
+        for col in 0..board.width {
+            let (x, y) = (col as i32 * SQUARE_PX, row as i32 * SQUARE_PX);
+            match board[Position::from_xy(col, row)] {
+                Square::Dot if vcs => canvas.fill(x + 1, y + 2, 2, 1, dot),
+                Square::Pill if vcs && blink => canvas.fill(x, y + 1, 4, 2, dot),
+                Square::Dot => canvas.fill(x + 1, y + 1, 2, 2, dot),
+                Square::Pill if blink => halfblock::disc(
+                    &mut canvas,
+                    x as f32 + 2.0,
+                    y as f32 + 2.0,
+                    2.0,
+                    style::Color::White,
+                ),
+                Square::Gate => canvas.fill(
+                    x,
+                    y + 2,
+                    4,
+                    1,
+                    style::Color::Rgb {
+                        r: 255,
+                        g: 184,
+                        b: 222,
+                    },
+                ),
+                _ => (),
+            }
+        }
+    }
+    canvas
+}
+
+// the centre of a square in pixels
+fn square_centre(pos: Position) -> (f32, f32) {
+    (
+        (pos.col() as i32 * SQUARE_PX + SQUARE_PX / 2) as f32,
+        (pos.row() as i32 * SQUARE_PX + SQUARE_PX / 2) as f32,
     )
 }
 
+// degrees - opening and closing as he moves
+fn player_mouth(anim_frame: usize) -> f32 {
+    [0.0, 35.0, 70.0, 100.0, 70.0, 35.0][anim_frame]
+}
+
+const SPRITE_RADIUS: f32 = 3.0;
+
+// pacman moves in pixels already - the arcade's 8 to a square
+fn draw_player_px(canvas: &mut Canvas, game: &Game, mouth: f32) {
+    let (x, y) = square_centre(game.player.pos);
+    let px = SQUARE_PX as f32 / crate::rules::PIXELS as f32;
+    let (ox, oy) = game.player.offset;
+    halfblock::pacman(
+        canvas,
+        x + ox as f32 * px,
+        y + oy as f32 * px,
+        SPRITE_RADIUS,
+        game.player.moving,
+        mouth,
+    );
+}
+
+fn ghost_color(i: usize) -> style::Color {
+    let rgb = |r, g, b| style::Color::Rgb { r, g, b };
+    match i {
+        0 => rgb(255, 184, 255), // Pinky
+        1 => rgb(255, 0, 0),     // Blinky
+        2 => rgb(0, 255, 255),   // Inky
+        _ => rgb(255, 184, 82),  // Clyde
+    }
+}
+
+const FRIGHT_BLUE: style::Color = style::Color::Rgb {
+    r: 33,
+    g: 33,
+    b: 255,
+};
+
+// ghosts move a square at a time - they are drawn on their way there from the last square, so
+// they glide from square to square
+fn draw_ghosts_px(canvas: &mut Canvas, game: &Game) {
+    let flash = game.rules.fright(game.level).1 as u128 * FLASH_MS;
+    let frame = (game.timecum / FRAME_MS as u128) as usize;
+    let white = style::Color::White;
+    for (i, g) in game.ghosts.iter().enumerate() {
+        if game.rules == Ruleset::Vcs && i % 2 != frame % 2 {
+            continue;
+        }
+        let (x, y) = square_centre(g.pos);
+        let (dx, dy) = (
+            g.pos.col() as i32 - g.prev.col() as i32,
+            g.pos.row() as i32 - g.prev.row() as i32,
+        );
+        let (dx, dy) = match (dx, dy) {
+            (-1 | 1, 0) | (0, -1 | 1) => (dx, dy),
+            // through the tunnel, from one side of the board to the other
+            (d, 0) if d.abs() == game.board.width as i32 - 1 => (-d.signum(), 0),
+            _ => (0, 0),
+        };
+        let behind =
+            (1.0 - g.progress as f32 / (100 * crate::rules::TILE_MS) as f32) * SQUARE_PX as f32;
+        let (x, y) = (x - dx as f32 * behind, y - dy as f32 * behind);
+        let outside = game.board[g.pos] != Square::House;
+        let (body, eyes) = match g.state {
+            GhostState::Dead => (None, (white, FRIGHT_BLUE)),
+            _ if outside
+                && (1..flash).contains(&g.edible_duration)
+                && g.edible_duration / (FLASH_MS / 2) % 2 == 1 =>
+            {
+                (Some(white), (style::Color::Red, style::Color::Red))
+            }
+            _ if outside && g.edible_duration > 0 => {
+                let peach = style::Color::Rgb {
+                    r: 255,
+                    g: 184,
+                    b: 174,
+                };
+                (Some(FRIGHT_BLUE), (peach, peach))
+            }
+            _ => (Some(ghost_color(i)), (white, FRIGHT_BLUE)),
+        };
+        let r = SPRITE_RADIUS;
+        halfblock::ghost(
+            canvas,
+            (x - r).round() as i32,
+            (y - r).round() as i32,
+            body,
+            eyes,
+            frame / 3,
+        );
+    }
+}
+
+// the intermission's pacmen and ghosts, in pixels
+fn draw_actor_px(canvas: &mut Canvas, actor: &Actor, stage: i32, frame: usize) {
+    let (x, y) = (
+        (actor.col * SQUARE_PX + SQUARE_PX / 2) as f32,
+        ((stage + actor.row) * SQUARE_PX + SQUARE_PX / 2) as f32,
+    );
+    let r = SPRITE_RADIUS;
+    let (left, top) = ((x - r) as i32, (y - r) as i32);
+    let white = style::Color::White;
+    match actor.sprite {
+        Sprite::Pacman(d) => halfblock::pacman(canvas, x, y, r, d, player_mouth(frame)),
+        Sprite::MsPacman(d) => {
+            halfblock::pacman(canvas, x, y, r, d, player_mouth(frame));
+            // and her bow
+            let bow = if d == Direction::Left {
+                left + 3
+            } else {
+                left + 1
+            };
+            canvas.fill(bow, top - 1, 2, 2, style::Color::Red);
+        }
+        Sprite::Ghost(i) => halfblock::ghost(
+            canvas,
+            left,
+            top,
+            Some(ghost_color(i)),
+            (white, FRIGHT_BLUE),
+            frame,
+        ),
+        Sprite::Scared => {
+            let peach = style::Color::Rgb {
+                r: 255,
+                g: 184,
+                b: 174,
+            };
+            halfblock::ghost(canvas, left, top, Some(FRIGHT_BLUE), (peach, peach), frame)
+        }
+        // three squares across - the column is his left edge
+        Sprite::Giant => halfblock::pacman(
+            canvas,
+            x + SQUARE_PX as f32,
+            y,
+            1.5 * SQUARE_PX as f32,
+            Direction::Right,
+            player_mouth(frame),
+        ),
+        _ => (),
+    }
+}
+
 pub enum InputEvent {
     Direction(Direction),
     Quit,
     Pause,
     Cheat,
+    Skip,
     Resize,
-    None,
 }
 
-pub fn poll_input() -> io::Result<InputEvent> {
-    if poll(Duration::from_millis(10))? {
+// all events that are waiting - so quick key presses in one frame are not lost.
+// Held keys repeat as presses, or as repeat events where the terminal reports those
+pub fn poll_input() -> io::Result<Vec<InputEvent>> {
+    let mut events = Vec::new();
+    while poll(Duration::ZERO)? {
         match read()? {
-            Event::Key(key_event) if key_event.kind == event::KeyEventKind::Press => {
-                return Ok(match key_event.code {
+            Event::Key(key_event) if key_event.kind != event::KeyEventKind::Release => {
+                events.push(match key_event.code {
                     KeyCode::Char('q') => InputEvent::Quit,
                     KeyCode::Char('v') => InputEvent::Cheat,
-                    KeyCode::Char(' ') => InputEvent::Pause,
+                    KeyCode::Char(' ') if key_event.kind == event::KeyEventKind::Press => {
+                        InputEvent::Pause
+                    }
                     KeyCode::Left => InputEvent::Direction(Direction::Left),
                     KeyCode::Right => InputEvent::Direction(Direction::Right),
                     KeyCode::Up => InputEvent::Direction(Direction::Up),
                     KeyCode::Down => InputEvent::Direction(Direction::Down),
-                    _ => InputEvent::None,
+                    // any other key - skips the intermissions
+                    _ if key_event.kind == event::KeyEventKind::Press => InputEvent::Skip,
+                    _ => continue,
                 });
             }
-            Event::Resize(_, _) => return Ok(InputEvent::Resize),
+            Event::Resize(_, _) => events.push(InputEvent::Resize),
             _ => {}
         }
     }
-    Ok(InputEvent::None)
+    Ok(events)
 }
 
-pub fn pause(game: &Game) -> io::Result<()> {
-    let mut w = io::BufWriter::new(stdout());
-    draw_message(&mut w, game, "PAUSED", false)?;
+pub fn pause(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
+    draw_message(r, game, "PAUSED", false)?;
     loop {
         if let Ok(Event::Key(key_event)) = read() {
             // Filter out Release/Repeat events for Windows compatibility
@@ -234,21 +686,16 @@ pub fn pause(game: &Game) -> io::Result<()> {
     }
 }
 
-pub fn another_game(game: &mut Game) -> io::Result<bool> {
+pub fn another_game(r: &mut dyn Renderer, game: &mut Game) -> io::Result<bool> {
     let s1 = "Another game, squire?";
     let s2 = "Y/N";
 
     loop {
-        let mut w = io::BufWriter::new(stdout());
-        draw_marquee(&mut w, game)?;
-        crossterm::queue!(
-            w,
-            cursor::MoveTo(centered_x(s1), 12),
-            style::PrintStyledContent(s1.red()),
-            cursor::MoveTo(centered_x(s2), 14),
-            style::PrintStyledContent(s2.red()),
-        )?;
-        w.flush()?;
+        draw_marquee(r, game)?;
+        let (x1, x2) = (centered_x(r, game, s1), centered_x(r, game, s2));
+        put(r, x1, 12, s1.red())?;
+        put(r, x2, 14, s2.red())?;
+        r.flush()?;
         game.mq_idx = (game.mq_idx + 1) % MARQUEE.len();
 
         if poll(Duration::from_millis(120))?
@@ -264,32 +711,33 @@ pub fn another_game(game: &mut Game) -> io::Result<bool> {
     }
 }
 
-pub fn render_game_info() -> io::Result<()> {
+pub fn render_game_info(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
     let s1: &str = "UniPac - Unicode-powered Pacman";
     let s2 = "Rusty Edition 2025 ";
 
-    crossterm::queue!(
-        stdout(),
-        terminal::Clear(terminal::ClearType::All),
-        cursor::MoveTo(centered_x(s1), 2),
-        style::PrintStyledContent(s1.cyan()),
-        cursor::MoveTo(centered_x(s2), 3),
-        style::PrintStyledContent(s2.yellow()),
-    )
+    r.clear()?;
+    let (x1, x2) = (centered_x(r, game, s1), centered_x(r, game, s2));
+    put(r, x1, 2, s1.cyan())?;
+    put(r, x2, 3, s2.yellow())
 }
 
-pub fn draw_death_frame<W: io::Write>(w: &mut W, game: &Game, frame_idx: usize) -> io::Result<()> {
+pub fn draw_death_frame(r: &mut dyn Renderer, game: &Game, frame_idx: usize) -> io::Result<()> {
+    // the mouth opens until there is nothing left of him
+    if game.layout == Layout::HalfBlock {
+        let mut canvas = board_canvas(game, false);
+        draw_player_px(&mut canvas, game, 40.0 * frame_idx as f32);
+        return canvas.draw(r, 0, 0);
+    }
     let animation = "|Vv_.+*X*+. ";
     let ch = animation.chars().nth(frame_idx).unwrap_or(' ');
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(game.player.pos.col() as u16, game.player.pos.row() as u16),
-        style::PrintStyledContent(ch.to_string().bold().yellow()), // Convert char to string for styling
-    )?;
-    Ok(())
+    let (col, row) = (
+        screen_col(game, game.player.pos.col()),
+        game.player.pos.row() as u16,
+    );
+    put(r, col, row, ch.bold().yellow())
 }
 
-pub fn render_rhs<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
+pub fn render_rhs(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
     // draw lives - ascii art, one pacman for each
     // let pacimg = ["/-\\", "|'<", "\\_/", "   ", "   ", "   "];
     // // need to remove the old pacman character in some cases
@@ -312,41 +760,49 @@ pub fn render_rhs<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
     } else {
         "  "
     };
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(game.board.width as u16 + 2, game.board.height as u16 - 1),
-        style::Print(s)
-    )?;
+    let (col, row) = (
+        board_cols(game) as u16 + 2,
+        screen_row(game, game.board.height - 1),
+    );
+    print(r, col, row, s)?;
 
-    let i = centered_x("Score : 123456"); // get a pos base on av score digits
-    crossterm::queue!(
-        w,
-        cursor::MoveTo(i, 5),
-        style::PrintStyledContent(format!("Maze   : {}", game.board.maze_name).bold().white()),
-        cursor::MoveTo(i, 7),
-        style::PrintStyledContent(format!("Score  : {}", game.score).bold().white()),
-        cursor::MoveTo(i, 8),
-        style::PrintStyledContent(format!("High   : {}", game.high_score).bold().white()),
-        cursor::MoveTo(i, 9),
-        style::PrintStyledContent(format!("Level  : {}", game.level + 1).bold().white()),
+    let i = centered_x(r, game, "Score : 123456"); // get a pos base on av score digits
+    put(
+        r,
+        i,
+        5,
+        format!("Maze   : {}", game.board.maze_name).bold().white(),
+    )?;
+    put(r, i, 7, format!("Score  : {}", game.score).bold().white())?;
+    put(
+        r,
+        i,
+        8,
+        format!("High   : {}", game.high_score).bold().white(),
+    )?;
+    put(
+        r,
+        i,
+        9,
+        format!("Level  : {}", game.level + 1).bold().white(),
     )?;
     draw_message_at(
-        w,
+        r,
         game,
         Position::from_xy(game.board.width - 1, game.board.height),
-        get_fruit_symbol(game.bonus()),
+        get_fruit_symbol(game.fruit),
     )?;
 
     let s = vec!['\u{1F642}'; game.lives as usize];
     let s1 = vec![' '; MAX_PACMAN_LIVES as usize - s.len()];
     let s2: String = s.into_iter().chain(s1).collect::<String>();
-    draw_message_at(w, game, Position::from_xy(0, game.board.height), &s2)?;
-    draw_marquee(w, game)
+    draw_message_at(r, game, Position::from_xy(0, game.board.height), &s2)?;
+    draw_marquee(r, game)
 }
 
-fn draw_marquee<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
+fn draw_marquee(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
     // scroll marquee
-    let (cols, rows) = terminal::size().unwrap_or((80, 24));
+    let (cols, rows) = r.size();
 
     let marquee_x = 0; // start column
     let q: u16 = cols.saturating_sub(1); // Subtract 1 to avoid the "last cell" scroll trigger
@@ -354,40 +810,238 @@ fn draw_marquee<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
     let t: usize = q as usize + game.mq_idx;
     let i2: usize = t % MARQUEE.len();
 
-    crossterm::queue!(w, cursor::MoveTo(marquee_x, rows - 1))?;
     if i1 < i2 {
-        crossterm::queue!(w, style::PrintStyledContent(MARQUEE[i1..i2].white()))
+        put(r, marquee_x, rows - 1, MARQUEE[i1..i2].white())
     } else {
         // marquee is assumed to be ascii (1 byte characters)
         let part1 = &MARQUEE[i1..];
         let part2 = &MARQUEE[0..i2.min(MARQUEE.len())];
-        crossterm::queue!(
-            w,
-            style::PrintStyledContent(format!("{part1}{part2}").white())
-        )
+        put(r, marquee_x, rows - 1, format!("{part1}{part2}").white())
     }
 }
 
-pub fn clear_screen() -> io::Result<()> {
-    crossterm::queue!(stdout(), terminal::Clear(terminal::ClearType::All))
+const SCARED: &str = "\u{1F631}";
+
+fn ghost_symbol(i: usize) -> &'static str {
+    match i {
+        0 => "\u{1F47A}",
+        1 => "\u{1F479}",
+        2 => "\u{1F47B}",
+        _ => "\u{1F383}",
+    }
 }
 
-pub fn draw_ghosts<W: Write>(w: &mut W, game: &Game) -> io::Result<()> {
+pub fn draw_ghosts(r: &mut dyn Renderer, game: &Game) -> io::Result<()> {
+    // flash before recovering from fright
+    let flash = game.rules.fright(game.level).1 as u128 * FLASH_MS;
+    // the VCS could only show two ghosts at a time - they take turns, so they all flicker
+    let frame = (game.timecum / FRAME_MS as u128) as usize;
     for (i, g) in game.ghosts.iter().enumerate() {
+        if game.rules == Ruleset::Vcs && i % 2 != frame % 2 {
+            continue;
+        }
         let s = match (g.state, game.board[g.pos] != Square::House, i) {
             (GhostState::Dead, _, _) => "\u{1F440}",
-            (_, true, _) if (1..2000).contains(&g.edible_duration) => "\u{1F47D}",
-            (_, true, _) if g.edible_duration > 0 => "\u{1F631}",
-            (_, _, 0) => "\u{1F47A}",
-            (_, _, 1) => "\u{1F479}",
-            (_, _, 2) => "\u{1F47B}",
-            (_, _, _) => "\u{1F383}",
+            (_, true, _)
+                if (1..flash).contains(&g.edible_duration)
+                    && g.edible_duration / (FLASH_MS / 2) % 2 == 1 =>
+            {
+                "\u{1F47D}"
+            }
+            (_, true, _) if g.edible_duration > 0 => SCARED,
+            (_, _, i) => ghost_symbol(i),
         };
-        crossterm::queue!(
-            w,
-            cursor::MoveTo(g.pos.col() as u16, g.pos.row() as u16),
-            style::Print(s)
-        )?;
+        print(r, screen_col(game, g.pos.col()), g.pos.row() as u16, s)?;
     }
     Ok(())
 }
+
+// the intermission is played on an empty board, with the same sprites as the game
+pub fn draw_cutscene(r: &mut dyn Renderer, game: &Game, scene: &Cutscene) -> io::Result<()> {
+    let stage = game.board.fruit.row() as i32;
+    let frame = (scene.elapsed / 60) as usize % 6;
+    let pixels = game.layout == Layout::HalfBlock;
+    if pixels {
+        let mut canvas = Canvas::new(
+            game.board.width as i32 * SQUARE_PX,
+            game.board.height as i32 * SQUARE_PX,
+        );
+        for actor in scene.actors() {
+            draw_actor_px(&mut canvas, &actor, stage, frame);
+        }
+        canvas.draw(r, 0, 0)?;
+    } else {
+        let blank = " ".repeat(board_cols(game));
+        for row in 0..game.board.height {
+            print(r, 0, row as u16, &blank)?;
+        }
+    }
+    let title = scene.act.title();
+    let col = ((board_cols(game) - title.len()) / 2) as u16;
+    put(r, col, screen_row(game, 2), title.bold().white())?;
+
+    for actor in scene.actors() {
+        let (glyph, width) = match actor.sprite {
+            Sprite::Pacman(_)
+            | Sprite::MsPacman(_)
+            | Sprite::Ghost(_)
+            | Sprite::Scared
+            | Sprite::Giant
+                if pixels =>
+            {
+                continue;
+            }
+            Sprite::Pacman(d) => (player_char(d, frame).to_string().bold().yellow(), 1),
+            Sprite::MsPacman(d) => (player_char(d, frame).to_string().bold().magenta(), 1),
+            Sprite::Ghost(i) => (ghost_symbol(i).to_string().reset(), 2),
+            Sprite::Scared => (SCARED.to_string().reset(), 2),
+            Sprite::Nail => ("+".to_string().white(), 1),
+            Sprite::Sheet => ("~".to_string().red(), 1),
+            Sprite::Torn => (format!("{}\u{1F9B5}", ghost_symbol(1)).reset(), 4),
+            Sprite::Naked => ("\u{1F9B5}".to_string().reset(), 2),
+            Sprite::Heart => ("\u{1F495}".to_string().reset(), 2),
+            Sprite::Stork => ("\u{1F426}".to_string().reset(), 2),
+            Sprite::Junior => ("\u{1F476}".to_string().reset(), 2),
+            Sprite::Giant => {
+                // the ascii art pacman from the old lives display, mouth opening and closing
+                let mouth = if frame < 3 { "|'<" } else { "|'-" };
+                for (dy, line) in ["/-\\", mouth, "\\_/"].iter().enumerate() {
+                    let row = stage + actor.row + dy as i32 - 1;
+                    draw_clipped(r, game, actor.col, row, line.bold().yellow(), 3)?;
+                }
+                continue;
+            }
+        };
+        draw_clipped(r, game, actor.col, stage + actor.row, glyph, width)?;
+    }
+    r.flush()
+}
+
+// sprites are only drawn while wholly on the board - col and row are on the board, width in
+// screen columns
+fn draw_clipped<D: std::fmt::Display>(
+    r: &mut dyn Renderer,
+    game: &Game,
+    col: i32,
+    row: i32,
+    s: style::StyledContent<D>,
+    width: i32,
+) -> io::Result<()> {
+    let (cols, rows) = game.layout.scale();
+    let (col, row) = (col * cols as i32, row * rows as i32);
+    if col < 0 || col + width > board_cols(game) as i32 || row < 0 {
+        return Ok(());
+    }
+    put(r, col as u16, row as u16, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{AM, SoundSet};
+    use crate::render::TextGrid;
+
+    #[test]
+    fn test_first_frame() {
+        let game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        // just wide enough for the board
+        let mut grid = TextGrid::new(28, 26);
+        draw_dynamic(&mut grid, &game).unwrap();
+        let board = [
+            "╔════════════╦╦════════════╗",
+            "║............╠╣............║",
+            "║.╔╦╦╗.╔╦╦╦╗.╠╣.╔╦╦╦╗.╔╦╦╗.║",
+            "║●╚╩╩╝.╚╩╩╩╝.╚╝.╚╩╩╩╝.╚╩╩╝●║",
+            "║..........................║",
+            "║.════.╔╗.═══╦╦═══.╔╗.════.║",
+            "║......╠╣....╠╣....╠╣......║",
+            "╚════╗.╠╬═══ ╚╝ ═══╬╣.╔════╝",
+            "     ║.╠╣          ╠╣.║",
+            "     ║.╠╣ ╔══──══╗ ╠╣.║",
+            "═════╝.╚╝ ║ 👺 👹║ ╚╝.╚═════",
+            "      .   ║      ║   .",
+            "═════╗.╔╗ ║ 👻 🎃║ ╔╗.╔═════",
+            "     ║.╠╣ ╚══════╝ ╠╣.║",
+            "     ║.╠╣          ╠╣.║",
+            "╔════╝.╚╝ ═══╦╦═══ ╚╝.╚════╗",
+            "║............╠╣............║",
+            "║.══╦╗.═════.╚╝.═════.╔╦══.║",
+            "║●..╠╣.......}........╠╣..●║",
+            "╠══.╚╝.╔╗.═══╦╦═══.╔╗.╚╝.══╣",
+            "║......╠╣....╠╣....╠╣......║",
+            "║.═════╩╩═══.╚╝.═══╩╩═════.║",
+            "║..........................║",
+            "╚══════════════════════════╝",
+        ];
+        for (row, line) in board.iter().enumerate() {
+            assert_eq!(grid.row(row as u16).trim_end(), *line, "row {row}");
+        }
+    }
+
+    #[test]
+    fn test_write_changes() {
+        let front = CellBuffer::new(10, 3);
+        let mut back = front.clone();
+        back.print(2, 1, "ab", ContentStyle::new());
+        back.print(7, 1, "c", ContentStyle::new());
+        let mut out = Vec::new();
+        write_changes(&mut out, &front, &back).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // one move for "ab", one to skip to "c", and nothing for the unchanged cells
+        assert_eq!(out.matches('H').count(), 2, "{out:?}");
+        assert!(out.contains("ab"));
+
+        let mut out = Vec::new();
+        write_changes(&mut out, &back, &back).unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains('H'));
+    }
+
+    #[test]
+    fn test_wide_frame() {
+        let mut game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        game.layout = Layout::Wide;
+        let mut grid = TextGrid::new(56, 26);
+        draw_dynamic(&mut grid, &game).unwrap();
+        assert_eq!(
+            grid.row(0).trim_end(),
+            "╔═════════════════════════╦═╦═════════════════════════╗"
+        );
+        // every square is two columns, and the ghosts fit in theirs
+        assert_eq!(
+            grid.row(18).trim_end(),
+            "║ ● . . ╠═╣ . . . . . . . } . . . . . . . . ╠═╣ . . ● ║"
+        );
+        assert_eq!(
+            grid.row(10).trim_end(),
+            "══════════╝ . ╚═╝   ║   👺    👹  ║   ╚═╝ . ╚══════════"
+        );
+    }
+
+    #[test]
+    fn test_half_block_frame() {
+        let mut game = Game::new(Ruleset::Classic, AM::new(SoundSet::Synth));
+        game.layout = Layout::HalfBlock;
+        // Blinky halfway from (5, 1) to (6, 1)
+        let g = &mut game.ghosts[1];
+        (g.prev, g.pos, g.direction) = (
+            Position::from_xy(5, 1),
+            Position::from_xy(6, 1),
+            Direction::Right,
+        );
+        g.state = GhostState::Outside;
+        g.progress = 50 * crate::rules::TILE_MS;
+        let mut grid = TextGrid::new(112, 50);
+        draw_dynamic(&mut grid, &game).unwrap();
+        let cells = |row: u16, col: usize, n: usize| -> String {
+            grid.row(row).chars().skip(col).take(n).collect()
+        };
+        // the outer wall's corner is rounded
+        assert_eq!(cells(0, 0, 4), "  ▄▄");
+        assert_eq!(cells(1, 0, 4), " ██▀");
+        // the dot at (1, 1) - two pixels square, in the middle of its square
+        assert_eq!(cells(2, 4, 4), " ▄▄ ");
+        assert_eq!(cells(3, 4, 4), " ▀▀ ");
+        // the ghost's eyes, two pixels short of (6, 1)
+        assert_eq!(cells(2, 21, 6), "█▀██▀█");
+    }
+}